@@ -1,21 +1,38 @@
+use crate::types::clock::Clock;
+use crate::types::keystroke::Keystroke;
 use crate::types::typing::Typing;
 use anyhow::Result;
-use encoding::all::ISO_8859_1;
-use encoding::{DecoderTrap, EncoderTrap, Encoding};
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
 const SELECTABLE_TIME: [&usize; 4] = [&15, &30, &60, &120];
+const SELECTABLE_WORDS: [usize; 4] = [10, 25, 50, 100];
+const SELECTABLE_LINES: [usize; 4] = [5, 10, 20, 40];
+
+// What ends a session: the time running out, a fixed amount of text, or the whole text.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Mode {
+    #[default]
+    Time,
+    Words(usize),
+    Lines(usize),
+    Zen,
+}
 
 #[derive(Clone, Debug)]
 pub struct App {
     pub time: Duration,
     pub typing: Typing,
+    pub mode: Mode,
     progress: TypingProgress,
     custom_time: Duration,
+    custom_count: usize,
+    text: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TypingResult {
+    pub elapsed: u64,
     pub wpm: usize,
     pub acc: usize,
     pub typed: usize,
@@ -23,22 +40,55 @@ pub struct TypingResult {
     pub wpm_max: f64,
     pub wpm_plot: Vec<(f64, f64)>,
     pub acc_plot: Vec<(f64, f64)>,
+    pub keystrokes: Vec<Keystroke>,
+    #[serde(skip)]
+    pub time: u64, // seconds selected for the session, which may differ from --time
+    #[serde(skip)]
+    pub mode: Mode,
 }
 
 impl App {
-    pub fn new(text: &str, remaining_time: Duration, display_lines: usize) -> Result<App> {
+    pub fn new(
+        text: &str,
+        remaining_time: Duration,
+        display_lines: usize,
+        force_correction: bool,
+    ) -> Result<App> {
         let text = App::filter_text(text);
-        let typing = Typing::new(&text, remaining_time, display_lines)?;
+        let typing = Typing::new(&text, remaining_time, display_lines, force_correction)?;
         Ok(App {
             typing,
             time: remaining_time,
+            mode: Mode::Time,
             custom_time: remaining_time,
+            custom_count: 0,
             progress: TypingProgress::new(),
+            text,
         })
     }
 
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self.custom_count = mode.count().unwrap_or(0);
+        self.typing = self
+            .typing
+            .reset(&mode.text(&self.text), self.time)
+            .with_time_limit(mode == Mode::Time);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.typing = self.typing.with_clock(clock);
+        self
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.typing.clock()
+    }
+
     pub fn result(&self) -> TypingResult {
         TypingResult {
+            elapsed: self.elapsed_time().as_secs(),
             wpm: self.typing.wpm(),
             acc: self.typing.acc(),
             typed: self.typing.typed(),
@@ -46,6 +96,9 @@ impl App {
             wpm_max: self.progress.wpm_max(),
             wpm_plot: self.progress.wpm_plot(),
             acc_plot: self.progress.acc_plot(),
+            keystrokes: self.typing.keystrokes(),
+            time: self.time.as_secs(),
+            mode: self.mode,
         }
     }
 
@@ -61,7 +114,7 @@ impl App {
     }
 
     pub fn restart(mut self, text: &str) -> Self {
-        let text = App::filter_text(text);
+        let text = self.mode.text(&App::filter_text(text));
         self.typing = self.typing.restart(&text, self.time);
         self
     }
@@ -71,6 +124,16 @@ impl App {
         self
     }
 
+    pub fn pause(mut self) -> Self {
+        self.typing = self.typing.pause();
+        self
+    }
+
+    pub fn resume(mut self) -> Self {
+        self.typing = self.typing.resume();
+        self
+    }
+
     pub fn input(mut self, c: char) -> Self {
         self.typing = self.typing.input(c);
         self
@@ -100,6 +163,51 @@ impl App {
         times
     }
 
+    pub fn selectable_count(&self) -> Vec<usize> {
+        let mut counts = [self.mode.presets().to_vec(), vec![self.custom_count]].concat();
+        counts.retain(|c| *c > 0);
+        counts.sort_unstable();
+        counts.dedup();
+        counts
+    }
+
+    pub fn next_preset(self) -> Self {
+        match self.mode {
+            Mode::Time => self.next_time(),
+            Mode::Zen => self,
+            _ => self.shift_count(true),
+        }
+    }
+
+    pub fn prev_preset(self) -> Self {
+        match self.mode {
+            Mode::Time => self.prev_time(),
+            Mode::Zen => self,
+            _ => self.shift_count(false),
+        }
+    }
+
+    fn shift_count(self, forward: bool) -> Self {
+        let counts = self.selectable_count();
+        let len = counts.len();
+        let i = counts
+            .iter()
+            .position(|c| Some(*c) == self.mode.count())
+            .unwrap_or(0);
+        let i = if forward {
+            (i + 1) % len
+        } else {
+            (i + len - 1) % len
+        };
+        let custom_count = self.custom_count;
+        let mode = self.mode.with_count(counts[i]);
+
+        App {
+            custom_count,
+            ..self.with_mode(mode)
+        }
+    }
+
     pub fn next_time(mut self) -> Self {
         let time = self.time.as_secs();
         let custom_time = self.custom_time.as_secs();
@@ -142,7 +250,7 @@ impl App {
             60 => 30,
             120 => 60,
             _ => {
-                if (15..=120).contains(&custom_time) {
+                if !(15..=120).contains(&custom_time) {
                     120
                 } else {
                     custom_time
@@ -155,16 +263,81 @@ impl App {
     }
 
     pub fn elapsed_time(&self) -> Duration {
-        self.time - Duration::from_secs(self.typing.get_remaining_time() as u64)
+        if self.typing.is_timed() {
+            self.time - Duration::from_secs(self.typing.get_remaining_time() as u64)
+        } else {
+            self.typing.elapsed()
+        }
     }
 
-    fn filter_text(text: &str) -> String {
-        let text = ISO_8859_1.encode(text, EncoderTrap::Ignore).unwrap();
-        ISO_8859_1
-            .decode(&text, DecoderTrap::Strict)
-            .unwrap()
-            .replace('\t', "    ")
-            .to_owned()
+    pub fn filter_text(text: &str) -> String {
+        text.replace("\r\n", "\n").replace('\t', "    ")
+    }
+}
+
+impl Mode {
+    pub fn new(words: Option<usize>, lines: Option<usize>, zen: bool) -> Self {
+        match (words, lines) {
+            (Some(n), _) => Mode::Words(n),
+            (_, Some(n)) => Mode::Lines(n),
+            _ if zen => Mode::Zen,
+            _ => Mode::Time,
+        }
+    }
+
+    pub fn count(&self) -> Option<usize> {
+        match self {
+            Mode::Time | Mode::Zen => None,
+            Mode::Words(n) | Mode::Lines(n) => Some(*n),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Time => "time",
+            Mode::Words(_) => "words",
+            Mode::Lines(_) => "lines",
+            Mode::Zen => "zen",
+        }
+    }
+
+    fn with_count(&self, n: usize) -> Self {
+        match self {
+            Mode::Words(_) => Mode::Words(n),
+            Mode::Lines(_) => Mode::Lines(n),
+            _ => *self,
+        }
+    }
+
+    fn presets(&self) -> &'static [usize] {
+        match self {
+            Mode::Time | Mode::Zen => &[],
+            Mode::Words(_) => &SELECTABLE_WORDS,
+            Mode::Lines(_) => &SELECTABLE_LINES,
+        }
+    }
+
+    // The part of the text a session of this mode covers.
+    pub fn text(&self, text: &str) -> String {
+        match self {
+            Mode::Time | Mode::Zen => text.to_owned(),
+            Mode::Words(n) => {
+                let mut words = 0;
+                let mut prev = ' ';
+
+                for (i, c) in text.char_indices() {
+                    if prev.is_whitespace() && !c.is_whitespace() {
+                        words += 1;
+                        if words > *n {
+                            return text[..i].trim_end().to_owned();
+                        }
+                    }
+                    prev = c;
+                }
+                text.to_owned()
+            }
+            Mode::Lines(n) => text.split('\n').take(*n).collect::<Vec<&str>>().join("\n"),
+        }
     }
 }
 
@@ -222,15 +395,17 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn start() {
-        let app = App::new("test", Duration::from_secs(10), 10).unwrap();
+        let app = App::new("test", Duration::from_secs(10), 10, false).unwrap();
         assert_eq!(app.clone().start().typing.is_before_start(), false);
         assert_eq!(app.clone().start().typing.is_finish(), false);
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn restart() {
-        let app = App::new("test", Duration::from_secs(10), 10).unwrap();
+        let app = App::new("test", Duration::from_secs(10), 10, false).unwrap();
         assert_eq!(
             app.clone()
                 .start()
@@ -242,30 +417,128 @@ mod tests {
         );
     }
 
+    #[test]
+    fn filter_text() {
+        assert_eq!(
+            App::filter_text("\tlet a = \"→ 日本語 🎉\";\r\n"),
+            "    let a = \"→ 日本語 🎉\";\n"
+        );
+    }
+
+    #[test]
+    fn mode_text() {
+        let text = "fn main() {\n    let a = 1;\n}\n";
+        assert_eq!(Mode::Time.text(text), text);
+        assert_eq!(Mode::Words(3).text(text), "fn main() {");
+        assert_eq!(Mode::Words(5).text(text), "fn main() {\n    let a");
+        assert_eq!(Mode::Words(100).text(text), text);
+        assert_eq!(Mode::Lines(2).text(text), "fn main() {\n    let a = 1;");
+    }
+
+    #[test]
+    fn words_mode() {
+        let app = App::new("a b c\nd", Duration::from_secs(10), 10, false)
+            .unwrap()
+            .with_mode(Mode::Words(2));
+        assert_eq!(app.typing.display_lines().len(), 1);
+
+        let app = app.start().input('a').tick().tick().tick();
+        assert_eq!(app.elapsed_time(), Duration::from_secs(3));
+        assert!(!app.typing.is_finish());
+
+        let app = app.input(' ').input('b');
+        assert!(app.typing.is_finish());
+        assert_eq!(app.result().elapsed, 3);
+    }
+
+    #[test]
+    fn zen_mode() {
+        let app = App::new("ab\nc", Duration::from_secs(1), 10, false)
+            .unwrap()
+            .with_mode(Mode::Zen)
+            .next_preset();
+        assert_eq!(app.mode, Mode::Zen);
+
+        let app = app.start().tick().tick().tick().input('a').input('b');
+        assert_eq!(app.elapsed_time(), Duration::from_secs(3));
+        assert_eq!(app.typing.current_line_index(), 1);
+        assert_eq!(app.typing.line_count(), 2);
+
+        let app = app.input('c');
+        assert!(app.typing.is_finish());
+    }
+
+    #[test]
+    fn pause() {
+        let clock = Clock::manual();
+        let app = App::new("fn main", Duration::from_secs(30), 10, false)
+            .unwrap()
+            .with_clock(clock.clone())
+            .start()
+            .input('f');
+        clock.advance(Duration::from_millis(500));
+        let app = app.pause();
+        clock.advance(Duration::from_secs(5));
+
+        let app = app.input('n').tick();
+        assert!(app.typing.is_paused());
+        assert_eq!(app.typing.typed(), 1);
+        assert_eq!(app.typing.get_remaining_time(), 30);
+
+        let app = app.resume();
+        clock.advance(Duration::from_millis(500));
+        let app = app.input('n').tick();
+        assert!(!app.typing.is_paused());
+        assert_eq!(app.typing.get_remaining_time(), 29);
+        assert_eq!(app.typing.keystrokes()[1].latency, 1000);
+    }
+
+    #[test]
+    fn next_preset() {
+        let app = App::new("test", Duration::from_secs(10), 10, false).unwrap();
+        assert_eq!(app.clone().next_preset().time, Duration::from_secs(15));
+
+        let app = app.with_mode(Mode::Lines(15));
+        assert_eq!(app.selectable_count(), vec![5, 10, 15, 20, 40]);
+        let app = app.next_preset();
+        assert_eq!(app.mode, Mode::Lines(20));
+        let app = app.next_preset().next_preset();
+        assert_eq!(app.mode, Mode::Lines(5));
+        let app = app.prev_preset();
+        assert_eq!(app.mode, Mode::Lines(40));
+        assert_eq!(app.selectable_count().len(), 5);
+
+        let app = App::new("test", Duration::from_secs(10), 10, false)
+            .unwrap()
+            .with_mode(Mode::Words(25))
+            .prev_preset();
+        assert_eq!(app.mode, Mode::Words(10));
+    }
+
     #[test]
     fn selectable_time() {
-        let app = App::new("test", Duration::from_secs(10), 10).unwrap();
+        let app = App::new("test", Duration::from_secs(10), 10, false).unwrap();
         assert_eq!(app.clone().selectable_time().len(), 5);
 
-        let app = App::new("test", Duration::from_secs(30), 10).unwrap();
+        let app = App::new("test", Duration::from_secs(30), 10, false).unwrap();
         assert_eq!(app.clone().selectable_time().len(), 4);
     }
 
     #[test]
     fn tick() {
-        let app = App::new("test", Duration::from_secs(10), 10).unwrap();
+        let app = App::new("test", Duration::from_secs(10), 10, false).unwrap();
         assert_eq!(app.start().tick().typing.get_remaining_time(), 9);
     }
 
     #[test]
     fn elapsed_time() {
-        let app = App::new("test", Duration::from_secs(10), 10).unwrap();
+        let app = App::new("test", Duration::from_secs(10), 10, false).unwrap();
         assert_eq!(app.start().tick().elapsed_time(), Duration::from_secs(1));
     }
 
     #[test]
     fn next_time_less_then_15() {
-        let app = App::new("test", Duration::from_secs(10), 10).unwrap();
+        let app = App::new("test", Duration::from_secs(10), 10, false).unwrap();
         let app = app.next_time();
         assert_eq!(app.time, Duration::from_secs(15));
 
@@ -284,7 +557,7 @@ mod tests {
 
     #[test]
     fn next_time_greater_than_120() {
-        let app = App::new("test", Duration::from_secs(240), 10).unwrap();
+        let app = App::new("test", Duration::from_secs(240), 10, false).unwrap();
         let app = app.next_time();
         assert_eq!(app.time, Duration::from_secs(15));
 
@@ -303,7 +576,7 @@ mod tests {
 
     #[test]
     fn prev_time_less_then_15() {
-        let app = App::new("test", Duration::from_secs(10), 10).unwrap();
+        let app = App::new("test", Duration::from_secs(10), 10, false).unwrap();
         let app = app.prev_time();
         assert_eq!(app.time, Duration::from_secs(120));
 
@@ -322,7 +595,7 @@ mod tests {
 
     #[test]
     fn prev_time_greater_than_120() {
-        let app = App::new("test", Duration::from_secs(240), 10).unwrap();
+        let app = App::new("test", Duration::from_secs(240), 10, false).unwrap();
         let app = app.prev_time();
         assert_eq!(app.time, Duration::from_secs(120));
 
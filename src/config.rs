@@ -0,0 +1,161 @@
+use crate::export::Format;
+use crate::pick::Strategy;
+use crate::views::keyboard::Layout;
+use crate::views::theme::Colors;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILE: &str = "config.toml";
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub time: Option<usize>,
+    pub line: Option<usize>,
+    pub extension: Option<String>,
+    pub theme: Option<String>,
+    pub layout: Option<String>,
+    pub format: Option<String>,
+    pub force_correction: Option<bool>,
+    pub max_file_size: Option<u64>,
+    pub max_line_length: Option<usize>,
+    pub pick: Option<String>,
+    pub colors: Colors,
+    pub keys: KeyBindings,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    pub quit: char,
+    pub restart: char,
+    pub exit: char,  // used with the control key
+    pub pause: char, // used with the control key
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            quit: 'q',
+            restart: 'r',
+            exit: 'c',
+            pause: 'p',
+        }
+    }
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("gitype").join(CONFIG_FILE))
+    }
+
+    // A missing file at the default location is not an error, an explicitly given one is.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        match path {
+            Some(path) => Config::from_file(path),
+            None => match Config::default_path() {
+                Some(path) if path.exists() => Config::from_file(&path),
+                _ => Ok(Config::default()),
+            },
+        }
+    }
+
+    fn from_file(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let config: Config = toml::from_str(&text)
+            .map_err(|e| anyhow!("Invalid config {}: {}", path.display(), e))?;
+        config
+            .validate()
+            .map_err(|e| anyhow!("Invalid config {}: {}", path.display(), e))?;
+        Ok(config)
+    }
+
+    // Rejects the values the command line does not accept either.
+    fn validate(&self) -> Result<()> {
+        let options = [
+            ("layout", &self.layout, &Layout::NAMES[..]),
+            ("format", &self.format, &Format::NAMES[..]),
+            ("pick", &self.pick, &Strategy::NAMES[..]),
+        ];
+
+        for (key, value, names) in options {
+            match value {
+                Some(value) if !names.contains(&value.as_str()) => {
+                    return Err(anyhow!(
+                        "{} = \"{}\" is not one of {}",
+                        key,
+                        value,
+                        names.join(", ")
+                    ))
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let config: Config = toml::from_str(
+            r##"
+time = 60
+extension = "rs"
+force_correction = true
+max_line_length = 200
+pick = "churn"
+
+[colors]
+fg = "#c0c0c0"
+entered = "blue"
+
+[keys]
+quit = "x"
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(config.time, Some(60));
+        assert_eq!(config.line, None);
+        assert_eq!(config.extension, Some("rs".to_owned()));
+        assert_eq!(config.force_correction, Some(true));
+        assert_eq!(config.max_line_length, Some(200));
+        assert_eq!(config.max_file_size, None);
+        assert_eq!(config.pick, Some("churn".to_owned()));
+        assert_eq!(config.colors.fg, Some("#c0c0c0".to_owned()));
+        assert_eq!(config.colors.entered, Some("blue".to_owned()));
+        assert_eq!(config.colors.bg, None);
+        assert_eq!(config.keys.quit, 'x');
+        assert_eq!(config.keys.restart, 'r');
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(toml::from_str::<Config>("unknown = 1").is_err());
+        assert!(toml::from_str::<Config>("[colors]\nunknown = \"red\"").is_err());
+    }
+
+    #[test]
+    fn validate() {
+        let config = |text: &str| toml::from_str::<Config>(text).unwrap().validate();
+        assert!(config("layout = \"dvorak\"\nformat = \"csv\"\npick = \"mine\"").is_ok());
+        assert_eq!(
+            config("layout = \"dvrak\"").unwrap_err().to_string(),
+            "layout = \"dvrak\" is not one of qwerty, dvorak, colemak"
+        );
+        assert!(config("format = \"xml\"").is_err());
+        assert!(config("pick = \"old\"").is_err());
+    }
+
+    #[test]
+    fn load_missing() {
+        assert!(Config::load(Some(Path::new("/nonexistent/gitype.toml"))).is_err());
+    }
+}
@@ -0,0 +1,378 @@
+pub mod script;
+
+use crate::app::{App, TypingResult};
+use crate::config::KeyBindings;
+use crate::types::clock::Clock;
+use crate::types::typing::Typing;
+use crate::views::{highlight::Highlight, keyboard::Keyboard, theme::Theme, view, Source};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{backend::Backend, Terminal};
+use script::Script;
+use std::io;
+use std::time::Duration;
+
+const ONE_SEC: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Control {
+    Continue,
+    Quit,
+}
+
+// The event loop of a session, independent of the terminal and the source of key events.
+pub struct Driver {
+    app: App,
+    text: String,
+    theme: Theme,
+    highlight: Highlight,
+    keyboard: Keyboard,
+    source: Source,
+    keys: KeyBindings,
+    clock: Clock,
+    last_tick: Duration,
+    results: Vec<TypingResult>,
+    recorded: bool,
+}
+
+impl Driver {
+    pub fn new(
+        app: App,
+        text: &str,
+        theme: Theme,
+        highlight: Highlight,
+        keyboard: Keyboard,
+        source: Source,
+        keys: KeyBindings,
+    ) -> Self {
+        let clock = app.clock();
+
+        Driver {
+            app,
+            text: text.to_owned(),
+            theme,
+            highlight,
+            keyboard,
+            source,
+            keys,
+            last_tick: clock.now(),
+            clock,
+            results: Vec::new(),
+            recorded: false,
+        }
+    }
+
+    // Records the result of a finished session once, then renders the current screen.
+    pub fn draw<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        if self.app.typing.is_finish() {
+            if !self.recorded && self.app.typing.typed() + self.app.typing.typo() > 0 {
+                self.results.push(self.app.result());
+            }
+            self.recorded = true;
+        } else {
+            self.recorded = false;
+        }
+
+        terminal.draw(|f| {
+            view(
+                f,
+                &self.app,
+                &self.theme,
+                &self.highlight,
+                &self.keyboard,
+                &self.source,
+                &self.keys,
+            )
+        })?;
+        Ok(())
+    }
+
+    // How long to wait for a key before the next tick is due.
+    pub fn timeout(&self) -> Duration {
+        ONE_SEC
+            .checked_sub(self.clock.now().saturating_sub(self.last_tick))
+            .unwrap_or_default()
+    }
+
+    pub fn tick(&mut self) {
+        let now = self.clock.now();
+
+        if now.saturating_sub(self.last_tick) >= ONE_SEC {
+            if let Typing::Running(_) = self.app.typing {
+                self.app = self.app.clone().tick();
+            }
+            self.last_tick = now;
+        }
+    }
+
+    pub fn handle(&mut self, key: KeyEvent) -> Control {
+        let app = self.app.clone();
+        let keys = &self.keys;
+        let exit = |c: char| c == keys.exit && key.modifiers == KeyModifiers::CONTROL;
+        let pause = |c: char| c == keys.pause && key.modifiers == KeyModifiers::CONTROL;
+
+        self.app = match app.typing {
+            Typing::BeforeStart(_) => match key.code {
+                KeyCode::Right => app.next_preset(),
+                KeyCode::Left => app.prev_preset(),
+                KeyCode::Char(c) if c == keys.quit || exit(c) => return Control::Quit,
+                KeyCode::Char(c) => app.start().input(c),
+                _ => app,
+            },
+            Typing::Running(_) => match key.code {
+                KeyCode::Enter => app.input('\n'),
+                KeyCode::Char(c) if exit(c) => app.finish(),
+                KeyCode::Char(c) if pause(c) => app.pause(),
+                KeyCode::Char(c) => app.input(c),
+                KeyCode::Backspace | KeyCode::Delete => app.backspace(),
+                _ => app,
+            },
+            Typing::Paused(_) => match key.code {
+                KeyCode::Char(c) if exit(c) => app.finish(),
+                KeyCode::Char(c) if pause(c) => app.resume(),
+                _ => app,
+            },
+            Typing::Finish(_) => match key.code {
+                KeyCode::Char(c) if c == keys.restart => app.restart(&self.text),
+                KeyCode::Tab => {
+                    self.keyboard = self.keyboard.toggle_metric();
+                    app
+                }
+                KeyCode::Char(c) if c == keys.quit || exit(c) => return Control::Quit,
+                _ => app,
+            },
+        };
+
+        Control::Continue
+    }
+
+    pub fn results(self) -> Vec<TypingResult> {
+        self.results
+    }
+
+    // Replays a script against the clock of the app, waking up for ticks the way
+    // a terminal waiting for keys would.
+    pub fn run_script<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        script: &Script,
+    ) -> io::Result<Control> {
+        for step in script.steps() {
+            let mut wait = step.delay;
+
+            loop {
+                self.draw(terminal)?;
+                let timeout = self.timeout();
+
+                if wait < timeout {
+                    self.clock.advance(wait);
+                    break;
+                }
+
+                self.clock.advance(timeout);
+                wait -= timeout;
+                self.tick();
+            }
+
+            if self.handle(step.key) == Control::Quit {
+                return Ok(Control::Quit);
+            }
+            self.tick();
+        }
+
+        self.draw(terminal)?;
+        Ok(Control::Continue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::views::keyboard::Layout;
+    use ratatui::backend::TestBackend;
+    use std::path::PathBuf;
+
+    const TEXT: &str = "fn main() {\n}";
+
+    fn run(script: &str, width: u16, height: u16) -> (Driver, Terminal<TestBackend>) {
+        let theme = Theme::new("dark").unwrap();
+        let app = App::new(TEXT, Duration::from_secs(30), 20, false)
+            .unwrap()
+            .with_clock(Clock::manual());
+        let highlight = Highlight::new(TEXT, &PathBuf::from("main.rs"), theme.syntax());
+        let mut driver = Driver::new(
+            app,
+            TEXT,
+            theme,
+            highlight,
+            Keyboard::new(Layout::Qwerty),
+            Source {
+                path: PathBuf::from("main.rs"),
+                subject: None,
+                first_line: None,
+                seed: None,
+            },
+            KeyBindings::default(),
+        );
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+
+        driver
+            .run_script(&mut terminal, &Script::parse(script).unwrap())
+            .unwrap();
+        (driver, terminal)
+    }
+
+    fn screen(terminal: &Terminal<TestBackend>) -> Vec<String> {
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deterministic_result() {
+        let script = "0 right\n100 \"fn main() {\"\n1500 \"}\"";
+        let results = run(script, 60, 20).0.results();
+        assert_eq!(results, run(script, 60, 20).0.results());
+        assert_eq!(results.len(), 1);
+
+        let result = &results[0];
+        assert_eq!(result.typed, 12);
+        assert_eq!(result.typo, 0);
+        assert_eq!(result.elapsed, 2);
+        assert_eq!(result.wpm, 72);
+        assert_eq!(result.keystrokes[1].latency, 100);
+        assert_eq!(result.keystrokes[11].latency, 1500);
+    }
+
+    #[test]
+    fn ticks() {
+        let (driver, _) = run("0 f\n2500 n\n0 x", 60, 20);
+        assert_eq!(driver.app.typing.get_remaining_time(), 28);
+        assert_eq!(driver.app.typing.typed(), 2);
+        assert_eq!(driver.app.typing.typo(), 1);
+        assert!(driver.results().is_empty());
+    }
+
+    #[test]
+    fn help_keys() {
+        let (mut driver, mut terminal) = run("", 60, 20);
+        driver.keys = KeyBindings {
+            quit: 'x',
+            restart: 'e',
+            exit: 'c',
+            pause: 's',
+        };
+        driver.draw(&mut terminal).unwrap();
+        assert_eq!(
+            screen(&terminal)[19],
+            "e to restart, x to quit, ctrl-s to pause, left, right to sel"
+        );
+    }
+
+    #[test]
+    fn quit() {
+        let mut driver = run("", 60, 20).0;
+        let quit = Script::parse("0 q").unwrap().steps()[0].key;
+        assert_eq!(driver.handle(quit), Control::Quit);
+    }
+
+    #[test]
+    fn snapshot_before_start() {
+        let (_, terminal) = run("0 right", 40, 20);
+        assert_eq!(
+            screen(&terminal).join("\n"),
+            r#"15 30 60 120
+fn main() {
+}
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+────────────────────────────────────────
+r to restart, q to quit, ctrl-p to pause"#
+        );
+    }
+
+    #[test]
+    fn snapshot_running() {
+        let (_, terminal) = run("0 \"fn mx\"\n1000 i", 40, 20);
+        assert_eq!(
+            screen(&terminal).join("\n"),
+            r#"29
+fn main() {
+}
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+────────────────────────────────────────
+wpm: 72 acc: 83% key: 6/1"#
+        );
+    }
+
+    #[test]
+    fn snapshot_finish() {
+        let (_, terminal) = run("100 \"fn mian() {}\"\n0 tab", 60, 30);
+        assert_eq!(
+            screen(&terminal).join("\n"),
+            r#"wpm: 144 acc: 83% key: 12/2
+
+────────────────────────────────────────────────────────────
+108│•
+   │•
+   │•
+54 │•
+   │•
+   │•
+0  │•
+   └────────────────────────────────────────────────────────
+   0                           0                           1
+slow keys     ␣ 100ms  ( 100ms  ) 100ms  a 100ms  i 100ms
+typo keys     a 1/1  i 1/1
+slow bigrams  ␣m 100ms  ␣{ 100ms  () 100ms  )␣ 100ms  ai 100
+typo bigrams  ai 1/1  ma 1/1
+
+heatmap: latency (tab to switch)
+ `   1   2   3   4   5   6   7   8   9   0   -   =
+   q   w   e   r   t   y   u   i   o   p   [   ]   \
+    a   s   d   f   g   h   j   k   l   ;   '   enter
+      z   x   c   v   b   n   m   ,   .   /
+
+
+────────────────────────────────────────────────────────────
+r to restart, q to quit
+main.rs
+
+
+"#
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::{anyhow, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Step {
+    pub delay: Duration, // since the previous step
+    pub key: KeyEvent,
+}
+
+// Timed keystrokes, one step per line:
+//
+//   # delay in milliseconds, then a key
+//   0 right
+//   150 f
+//   120 ctrl-c
+//   100 "fn main"   (each character after the delay)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Script {
+    steps: Vec<Step>,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Script::parse(&text).map_err(|e| anyhow!("Invalid script {}: {}", path.display(), e))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut steps = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (delay, key) = line
+                .split_once(' ')
+                .ok_or_else(|| anyhow!("line {}: expected `<delay> <key>`", i + 1))?;
+            let delay = Duration::from_millis(
+                delay
+                    .parse()
+                    .map_err(|_| anyhow!("line {}: invalid delay: {}", i + 1, delay))?,
+            );
+            let key = key.trim_start();
+
+            match key.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+                Some(text) => steps.extend(text.chars().map(|c| Step {
+                    delay,
+                    key: KeyEvent::from(KeyCode::Char(c)),
+                })),
+                None => steps.push(Step {
+                    delay,
+                    key: Script::key(key)
+                        .ok_or_else(|| anyhow!("line {}: unknown key: {}", i + 1, key))?,
+                }),
+            }
+        }
+
+        Ok(Script { steps })
+    }
+
+    fn key(key: &str) -> Option<KeyEvent> {
+        if let Some(c) = key.strip_prefix("ctrl-") {
+            return Script::single_char(c).map(|c| KeyEvent::new(c, KeyModifiers::CONTROL));
+        }
+
+        match key {
+            "space" => Some(KeyCode::Char(' ')),
+            "enter" => Some(KeyCode::Enter),
+            "tab" => Some(KeyCode::Tab),
+            "backspace" => Some(KeyCode::Backspace),
+            "delete" => Some(KeyCode::Delete),
+            "left" => Some(KeyCode::Left),
+            "right" => Some(KeyCode::Right),
+            "esc" => Some(KeyCode::Esc),
+            _ => Script::single_char(key),
+        }
+        .map(KeyEvent::from)
+    }
+
+    fn single_char(key: &str) -> Option<KeyCode> {
+        let mut chars = key.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(KeyCode::Char(c)),
+            _ => None,
+        }
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let script = Script::parse("# comment\n0 right\n\n150 \"ab\"\n20 ctrl-c\n5 space").unwrap();
+        let keys: Vec<(u64, KeyEvent)> = script
+            .steps()
+            .iter()
+            .map(|s| (s.delay.as_millis() as u64, s.key))
+            .collect();
+
+        assert_eq!(
+            keys,
+            vec![
+                (0, KeyEvent::from(KeyCode::Right)),
+                (150, KeyEvent::from(KeyCode::Char('a'))),
+                (150, KeyEvent::from(KeyCode::Char('b'))),
+                (20, KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+                (5, KeyEvent::from(KeyCode::Char(' '))),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Script::parse("right").is_err());
+        assert!(Script::parse("x right").is_err());
+        assert!(Script::parse("0 unknown").is_err());
+        assert!(Script::load(Path::new("/nonexistent/script")).is_err());
+    }
+}
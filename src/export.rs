@@ -0,0 +1,212 @@
+use crate::app::{Mode, TypingResult};
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Settings {
+    pub time: usize,
+    pub line: usize,
+    pub words: Option<usize>,
+    pub lines: Option<usize>,
+    pub zen: bool,
+    pub force_correction: bool,
+    pub extension: Option<String>,
+    pub lang: Vec<String>,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub theme: String,
+    pub layout: String,
+    pub commit: Option<String>,
+    pub rev: Option<String>,
+    pub drill: bool,
+    pub snippet: Option<usize>,
+    pub function: bool,
+    pub pick: String,
+    pub seed: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct Record<'a> {
+    file: &'a Path,
+    settings: Settings,
+    #[serde(flatten)]
+    result: &'a TypingResult,
+}
+
+impl Settings {
+    // The settings of a session, with what was selected before it started.
+    fn session(&self, result: &TypingResult) -> Settings {
+        Settings {
+            time: result.time as usize,
+            words: match result.mode {
+                Mode::Words(n) => Some(n),
+                _ => None,
+            },
+            lines: match result.mode {
+                Mode::Lines(n) => Some(n),
+                _ => None,
+            },
+            zen: result.mode == Mode::Zen,
+            ..self.clone()
+        }
+    }
+}
+
+impl Format {
+    pub const NAMES: [&'static str; 2] = ["json", "csv"];
+
+    pub fn new(format: &str) -> Self {
+        match format {
+            "csv" => Format::Csv,
+            _ => Format::Json,
+        }
+    }
+}
+
+pub fn export(
+    path: &Path,
+    format: &Format,
+    file: &Path,
+    settings: &Settings,
+    results: &[TypingResult],
+) -> Result<()> {
+    let text = match format {
+        Format::Json => to_json(file, settings, results)?,
+        Format::Csv => to_csv(file, settings, results),
+    };
+    fs::write(path, text)?;
+    Ok(())
+}
+
+fn to_json(file: &Path, settings: &Settings, results: &[TypingResult]) -> Result<String> {
+    let records: Vec<Record> = results
+        .iter()
+        .map(|result| Record {
+            file,
+            settings: settings.session(result),
+            result,
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&records)?)
+}
+
+// One row per second of each session, the summary columns are repeated on every row.
+fn to_csv(file: &Path, settings: &Settings, results: &[TypingResult]) -> String {
+    let header = "session,file,time,line,force_correction,elapsed,wpm,acc,typed,typo,second,second_wpm,second_acc";
+    let rows =
+        results.iter().enumerate().flat_map(|(i, result)| {
+            let settings = settings.session(result);
+            result.wpm_plot.iter().zip(result.acc_plot.iter()).map(
+                move |((second, wpm), (_, acc))| {
+                    format!(
+                        "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                        i + 1,
+                        escape(&file.to_string_lossy()),
+                        settings.time,
+                        settings.line,
+                        settings.force_correction,
+                        result.elapsed,
+                        result.wpm,
+                        result.acc,
+                        result.typed,
+                        result.typo,
+                        second,
+                        wpm,
+                        acc
+                    )
+                },
+            )
+        });
+
+    [header.to_owned()]
+        .into_iter()
+        .chain(rows)
+        .collect::<Vec<String>>()
+        .join("\n")
+        + "\n"
+}
+
+fn escape(value: &str) -> String {
+    if value.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn settings() -> Settings {
+        Settings {
+            time: 30,
+            line: 20,
+            words: None,
+            lines: Some(10),
+            zen: false,
+            force_correction: false,
+            extension: Some("rs".to_owned()),
+            lang: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            theme: "dark".to_owned(),
+            layout: "qwerty".to_owned(),
+            commit: None,
+            rev: None,
+            drill: false,
+            snippet: None,
+            function: false,
+            pick: "random".to_owned(),
+            seed: Some(42),
+        }
+    }
+
+    fn result() -> TypingResult {
+        TypingResult {
+            elapsed: 2,
+            wpm: 40,
+            acc: 90,
+            typed: 9,
+            typo: 1,
+            wpm_max: 48.0,
+            wpm_plot: vec![(0.0, 0.0), (1.0, 48.0)],
+            acc_plot: vec![(0.0, 100.0), (1.0, 90.0)],
+            keystrokes: Vec::new(),
+            time: 60,
+            mode: Mode::Lines(20),
+        }
+    }
+
+    #[test]
+    fn json() {
+        let json = to_json(&PathBuf::from("src/main.rs"), &settings(), &[result()]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value[0]["file"], "src/main.rs");
+        assert_eq!(value[0]["settings"]["time"], 60);
+        assert_eq!(value[0]["settings"]["lines"], 20);
+        assert_eq!(value[0]["settings"]["words"], serde_json::Value::Null);
+        assert_eq!(value[0]["wpm"], 40);
+        assert_eq!(value[0]["acc_plot"][1][1], 90.0);
+    }
+
+    #[test]
+    fn csv() {
+        let csv = to_csv(&PathBuf::from("src/a,b.rs"), &settings(), &[result()]);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "1,\"src/a,b.rs\",60,20,false,2,40,90,9,1,0,0,100");
+        assert_eq!(lines[2], "1,\"src/a,b.rs\",60,20,false,2,40,90,9,1,1,48,90");
+    }
+}
@@ -0,0 +1,331 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub const MAX_FILE_SIZE: u64 = 512 * 1024;
+pub const MAX_LINE_LENGTH: usize = 400;
+
+pub const ATTRIBUTES_FILE: &str = ".gitattributes";
+const ATTRIBUTES: [&str; 2] = ["linguist-generated", "linguist-vendored"];
+const LOCK_FILES: [&str; 4] = [
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "pnpm-lock.yaml",
+    "go.sum",
+];
+const SKIPPED_EXTENSIONS: [&str; 3] = ["lock", "map", "svg"];
+const VENDORED_DIRS: [&str; 4] = ["vendor", "node_modules", "third_party", "bower_components"];
+const GENERATED_MARKERS: [&str; 4] = [
+    "@generated",
+    "DO NOT EDIT",
+    "auto-generated",
+    "autogenerated",
+];
+const HEADER_LINES: usize = 5;
+const BINARY_PEEK: usize = 8000;
+pub const CONTENT_PEEK: u64 = 64 * 1024; // bytes of a file checked for binary and generated text
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limits {
+    pub max_file_size: u64,
+    pub max_line_length: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_file_size: MAX_FILE_SIZE,
+            max_line_length: MAX_LINE_LENGTH,
+        }
+    }
+}
+
+// Rejects files that are not worth typing: binary, minified, lock, generated and
+// vendored files, including those marked in .gitattributes.
+pub struct FileFilter {
+    root: PathBuf,
+    limits: Limits,
+    attributes: Vec<(PathBuf, Gitignore)>, // marked paths of each .gitattributes, deepest first
+}
+
+impl FileFilter {
+    // Reads .gitattributes in `root` and its parents up to the root of the repository.
+    pub fn new(root: &Path, limits: Limits) -> Self {
+        let dir = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let repository = dir.ancestors().position(|d| d.join(".git").exists());
+        let attributes = dir
+            .ancestors()
+            .take(repository.map(|p| p + 1).unwrap_or(1))
+            .filter_map(FileFilter::read_attributes)
+            .collect();
+
+        FileFilter {
+            root: dir,
+            limits,
+            attributes,
+        }
+    }
+
+    // Files that are not in the file system, like those of an archive, with none of the
+    // .gitattributes around `root`.
+    pub fn without_attributes(root: &Path, limits: Limits) -> Self {
+        FileFilter {
+            root: root.to_path_buf(),
+            limits,
+            attributes: Vec::new(),
+        }
+    }
+
+    // Reads .gitattributes in a directory below the root, relative to it, while walking the
+    // tree. Its attributes take precedence over those of its parents.
+    pub fn add_attributes(&mut self, dir: &Path) {
+        if dir.as_os_str().is_empty() {
+            return;
+        }
+        if let Some(attributes) = FileFilter::read_attributes(&self.root.join(dir)) {
+            self.attributes.insert(0, attributes);
+        }
+    }
+
+    // Adds the text of a .gitattributes in a directory relative to the root, after those of
+    // its parents.
+    pub fn add_attributes_text(&mut self, dir: &Path, text: &str) {
+        let dir = self.root.join(dir);
+        let attributes = FileFilter::parse_attributes(&dir, text);
+        self.attributes.insert(0, (dir, attributes));
+    }
+
+    fn read_attributes(dir: &Path) -> Option<(PathBuf, Gitignore)> {
+        fs::read_to_string(dir.join(ATTRIBUTES_FILE))
+            .ok()
+            .map(|text| (dir.to_path_buf(), FileFilter::parse_attributes(dir, &text)))
+    }
+
+    fn parse_attributes(dir: &Path, text: &str) -> Gitignore {
+        let mut builder = GitignoreBuilder::new(dir);
+
+        for line in text.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let pattern = match fields.next() {
+                Some(pattern) => pattern,
+                None => continue,
+            };
+
+            for attribute in fields {
+                let set = match attribute.split_once('=') {
+                    Some((name, value)) if ATTRIBUTES.contains(&name) => Some(value != "false"),
+                    None if ATTRIBUTES.contains(&attribute) => Some(true),
+                    None if ATTRIBUTES.contains(&attribute.trim_start_matches(['-', '!'])) => {
+                        Some(false)
+                    }
+                    _ => None,
+                };
+
+                if let Some(set) = set {
+                    let line = if set {
+                        pattern.to_owned()
+                    } else {
+                        format!("!{}", pattern)
+                    };
+                    let _ = builder.add_line(None, &line);
+                }
+            }
+        }
+
+        builder.build().unwrap_or_else(|_| Gitignore::empty())
+    }
+
+    // Checks that only need the path, relative to the root.
+    pub fn accepts_path(&self, path: &Path) -> bool {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        let vendored = path.components().any(|c| {
+            c.as_os_str()
+                .to_str()
+                .map(|c| VENDORED_DIRS.contains(&c))
+                .unwrap_or(false)
+        });
+
+        !LOCK_FILES.contains(&name.as_str())
+            && !SKIPPED_EXTENSIONS.contains(&extension.as_str())
+            && !name.contains(".min.")
+            && !vendored
+            && !self.is_marked(path)
+    }
+
+    // Checks the path and the content of a file, relative to the root.
+    pub fn accepts(&self, path: &Path) -> bool {
+        self.accepts_path(path) && self.accepts_file(&self.root.join(path))
+    }
+
+    fn accepts_file(&self, path: &Path) -> bool {
+        match fs::metadata(path) {
+            Ok(metadata) if self.accepts_size(metadata.len()) => (),
+            _ => return false,
+        }
+
+        let mut bytes = Vec::new();
+        match File::open(path).and_then(|f| f.take(CONTENT_PEEK).read_to_end(&mut bytes)) {
+            Ok(_) => self.accepts_prefix(&bytes),
+            Err(_) => false,
+        }
+    }
+
+    // Checks at most the first CONTENT_PEEK bytes of a file, cut at the last whole line when
+    // the file is longer.
+    pub fn accepts_prefix(&self, bytes: &[u8]) -> bool {
+        if (bytes.len() as u64) < CONTENT_PEEK {
+            return self.accepts_content(bytes);
+        }
+        match bytes.iter().rposition(|b| *b == b'\n') {
+            Some(end) => self.accepts_content(&bytes[..end]),
+            None => false,
+        }
+    }
+
+    pub fn accepts_size(&self, size: u64) -> bool {
+        size <= self.limits.max_file_size
+    }
+
+    // Rejects binary, minified and generated text.
+    fn accepts_content(&self, bytes: &[u8]) -> bool {
+        if !self.accepts_size(bytes.len() as u64) || bytes.iter().take(BINARY_PEEK).any(|b| *b == 0)
+        {
+            return false;
+        }
+
+        match std::str::from_utf8(bytes) {
+            Ok(text) => {
+                !text
+                    .lines()
+                    .any(|l| l.chars().count() > self.limits.max_line_length)
+                    && !text
+                        .lines()
+                        .take(HEADER_LINES)
+                        .any(|l| GENERATED_MARKERS.iter().any(|m| l.contains(m)))
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn is_marked(&self, path: &Path) -> bool {
+        let path = self.root.join(path);
+
+        self.attributes
+            .iter()
+            .filter(|(dir, _)| path.starts_with(dir))
+            .map(|(_, attributes)| attributes.matched_path_or_any_parents(&path, false))
+            .find(|m| !m.is_none())
+            .map(|m| m.is_ignore())
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn accepts_path() {
+        let filter = FileFilter::new(Path::new("/nonexistent"), Limits::default());
+        assert!(filter.accepts_path(Path::new("src/main.rs")));
+        assert!(!filter.accepts_path(Path::new("Cargo.lock")));
+        assert!(!filter.accepts_path(Path::new("web/package-lock.json")));
+        assert!(!filter.accepts_path(Path::new("dist/app.min.js")));
+        assert!(!filter.accepts_path(Path::new("logo.svg")));
+        assert!(!filter.accepts_path(Path::new("vendor/lib/a.go")));
+    }
+
+    #[test]
+    fn accepts() {
+        let dir = TempDir::new("filter-accepts");
+        let write = |path: &str, content: &[u8]| {
+            dir.file(path, content);
+        };
+        write(
+            ".gitattributes",
+            b"# comment\nsrc/gen/** linguist-generated\nsrc/gen/keep.rs -linguist-generated\n*.pb.go linguist-vendored=true\n",
+        );
+        write("src/gen/.gitattributes", b"b.rs -linguist-generated\n");
+        write("src/main.rs", b"fn main() {}\n");
+        write("src/gen/a.rs", b"fn a() {}\n");
+        write("src/gen/b.rs", b"fn b() {}\n");
+        write("src/gen/keep.rs", b"fn keep() {}\n");
+        write("src/api.pb.go", b"package api\n");
+        write("src/long.js", "x".repeat(50).as_bytes());
+        write("src/big.rs", "fn big() {}\n".repeat(10).as_bytes());
+        write("src/bin.rs", b"fn\0main");
+        write("src/latin1.rs", b"// caf\xe9\n");
+        write(
+            "src/types.rs",
+            b"// Code generated by protoc. DO NOT EDIT.\n",
+        );
+
+        let mut filter = FileFilter::new(
+            dir.path(),
+            Limits {
+                max_file_size: 100,
+                max_line_length: 40,
+            },
+        );
+        assert!(!filter.accepts(Path::new("src/gen/b.rs")));
+        filter.add_attributes(Path::new("src/gen"));
+        let accepted: Vec<&str> = [
+            "src/main.rs",
+            "src/gen/a.rs",
+            "src/gen/b.rs",
+            "src/gen/keep.rs",
+            "src/api.pb.go",
+            "src/long.js",
+            "src/big.rs",
+            "src/bin.rs",
+            "src/latin1.rs",
+            "src/types.rs",
+            "src/missing.rs",
+        ]
+        .into_iter()
+        .filter(|p| filter.accepts(Path::new(p)))
+        .collect();
+        assert_eq!(
+            accepted,
+            vec!["src/main.rs", "src/gen/b.rs", "src/gen/keep.rs"]
+        );
+    }
+
+    #[test]
+    fn attributes_text() {
+        let mut filter = FileFilter::without_attributes(Path::new("sdk.zip"), Limits::default());
+        assert!(filter.accepts_path(Path::new("gen/a.rs")));
+
+        filter.add_attributes_text(Path::new(""), "gen/** linguist-generated\n");
+        filter.add_attributes_text(Path::new("gen"), "keep.rs -linguist-generated\n");
+        assert!(!filter.accepts_path(Path::new("gen/a.rs")));
+        assert!(filter.accepts_path(Path::new("gen/keep.rs")));
+        assert!(filter.accepts_path(Path::new("src/a.rs")));
+    }
+
+    #[test]
+    fn accepts_large_file() {
+        let dir = TempDir::new("filter-large");
+        let line = format!("{}\n", "x".repeat(99));
+        dir.file("a.rs", line.repeat(1000));
+        dir.file("b.js", "x".repeat(100_000));
+
+        let filter = FileFilter::new(dir.path(), Limits::default());
+        assert!(filter.accepts(Path::new("a.rs")));
+        assert!(!filter.accepts(Path::new("b.js")));
+    }
+}
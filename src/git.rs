@@ -0,0 +1,66 @@
+use anyhow::{anyhow, Result};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+pub fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    git_bytes(dir, args).map(|output| String::from_utf8_lossy(&output).to_string())
+}
+
+pub fn git_bytes(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(anyhow!(String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .to_string()))
+    }
+}
+
+// Runs git with `input` written to its stdin, for batch commands, and passes its output to
+// `read` as it comes instead of holding all of it.
+pub fn git_with_input<T>(
+    dir: &Path,
+    args: &[&str],
+    input: Vec<u8>,
+    read: impl FnOnce(&mut dyn BufRead) -> Result<T>,
+) -> Result<T> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("Failed to open stdin of git."))?;
+    // written from another thread so that a full stdout can not block the input
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let mut stdout = BufReader::new(
+        child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("Failed to open stdout of git."))?,
+    );
+    let value = read(&mut stdout);
+    // closed before waiting, so that git stops when `read` did not read all of the output
+    drop(stdout);
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() && !output.stderr.is_empty() {
+        return Err(anyhow!(String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .to_string()));
+    }
+    let value = value?;
+    writer
+        .join()
+        .map_err(|_| anyhow!("Failed to write to git."))??;
+    Ok(value)
+}
@@ -0,0 +1,200 @@
+pub mod stats;
+
+use crate::app::TypingResult;
+use crate::types::keystroke::{KeyStats, Keystroke};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub path: PathBuf,
+    pub extension: String,
+    pub duration: u64,
+    pub timestamp: u64,
+    pub wpm: usize,
+    pub acc: usize,
+    pub typed: usize,
+    pub typo: usize,
+    pub wpm_plot: Vec<(f64, f64)>,
+    #[serde(default)]
+    pub keystrokes: Vec<Keystroke>,
+    #[serde(default)]
+    pub drill: bool,
+}
+
+impl Session {
+    pub fn new(result: &TypingResult, path: &Path, drill: bool) -> Self {
+        Session {
+            path: path.to_path_buf(),
+            extension: path
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("")
+                .to_lowercase(),
+            duration: result.elapsed,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            wpm: result.wpm,
+            acc: result.acc,
+            typed: result.typed,
+            typo: result.typo,
+            wpm_plot: result.wpm_plot.clone(),
+            keystrokes: result.keystrokes.clone(),
+            drill,
+        }
+    }
+}
+
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: PathBuf) -> Self {
+        History { path }
+    }
+
+    pub fn open() -> Result<Self> {
+        dirs::data_dir()
+            .map(|dir| History::new(dir.join("gitype").join(HISTORY_FILE)))
+            .ok_or_else(|| anyhow!("Data directory not found."))
+    }
+
+    pub fn save(&self, sessions: &[Session]) -> Result<()> {
+        if sessions.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        for session in sessions {
+            writeln!(file, "{}", serde_json::to_string(session)?)?;
+        }
+
+        Ok(())
+    }
+
+    // Key stats of every session, counted separately and merged.
+    pub fn key_stats(&self) -> Result<KeyStats> {
+        Ok(KeyStats::merge(
+            self.load()?.iter().map(|s| KeyStats::new(&s.keystrokes)),
+        ))
+    }
+
+    // Lines that can not be parsed, e.g. cut off by a crash while writing, are skipped.
+    pub fn load(&self) -> Result<Vec<Session>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(fs::read_to_string(&self.path)?
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .filter_map(|(i, l)| match serde_json::from_str(l) {
+                Ok(session) => Some(session),
+                Err(e) => {
+                    eprintln!("Skipped line {} of {}: {}", i + 1, self.path.display(), e);
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Mode;
+    use crate::testing::TempDir;
+
+    fn result(wpm: usize) -> TypingResult {
+        TypingResult {
+            elapsed: 30,
+            wpm,
+            acc: 90,
+            typed: 9,
+            typo: 1,
+            wpm_max: wpm as f64,
+            wpm_plot: vec![(0.0, 0.0), (1.0, wpm as f64)],
+            acc_plot: vec![(0.0, 100.0), (1.0, 90.0)],
+            keystrokes: vec![Keystroke::new("a", 'a', 100, true)],
+            time: 30,
+            mode: Mode::Time,
+        }
+    }
+
+    #[test]
+    fn new_session() {
+        let session = Session::new(&result(40), &PathBuf::from("src/Main.RS"), false);
+        assert_eq!(session.extension, "rs");
+        assert_eq!(session.duration, 30);
+        assert_eq!(session.wpm, 40);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("gitype-history-{}", std::process::id()))
+            .join(HISTORY_FILE);
+        let history = History::new(path.clone());
+        let sessions = vec![
+            Session::new(&result(40), &PathBuf::from("a.rs"), false),
+            Session::new(&result(50), &PathBuf::from("b.ts"), true),
+        ];
+
+        assert!(history.load().unwrap().is_empty());
+        history.save(&sessions).unwrap();
+        history.save(&sessions[..1]).unwrap();
+
+        let loaded = history.load().unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded[0], sessions[0]);
+        assert_eq!(loaded[2], sessions[0]);
+        assert!(loaded[1].drill);
+        assert_eq!(history.key_stats().unwrap().keys()[0].count, 3);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn load_corrupt() {
+        let dir = TempDir::new("history-corrupt");
+        let session = Session::new(&result(40), &PathBuf::from("a.rs"), false);
+        let line = serde_json::to_string(&session).unwrap();
+        let path = dir.file(
+            HISTORY_FILE,
+            format!("{}\n{}\n\n{}\n", line, &line[..line.len() / 2], line),
+        );
+
+        assert_eq!(
+            History::new(path).load().unwrap(),
+            vec![session.clone(), session]
+        );
+    }
+
+    #[test]
+    fn load_without_keystrokes() {
+        let session: Session = serde_json::from_str(
+            r#"{"path":"a.rs","extension":"rs","duration":30,"timestamp":0,"wpm":40,"acc":90,"typed":9,"typo":1,"wpm_plot":[]}"#,
+        )
+        .unwrap();
+        assert!(session.keystrokes.is_empty());
+        assert!(!session.drill);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::history::Session;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+
+const WEEK: u64 = 60 * 60 * 24 * 7;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub sessions: usize,
+    pub duration: u64,
+    pub keys: usize,
+    pub typo: usize,
+    pub avg_wpm: usize,
+    pub avg_acc: usize,
+    pub best_wpm: usize,
+    pub best_acc: usize,
+    pub trend: Option<i64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Stats {
+    pub total: Summary,
+    pub languages: Vec<(String, Summary)>,
+}
+
+impl Summary {
+    pub fn new(sessions: &[&Session], now: u64) -> Self {
+        let count = sessions.len().max(1);
+        let recent: Vec<&Session> = sessions
+            .iter()
+            .filter(|s| now.saturating_sub(s.timestamp) < WEEK)
+            .copied()
+            .collect();
+        let previous: Vec<&Session> = sessions
+            .iter()
+            .filter(|s| (WEEK..WEEK * 2).contains(&now.saturating_sub(s.timestamp)))
+            .copied()
+            .collect();
+
+        Summary {
+            sessions: sessions.len(),
+            duration: sessions.iter().map(|s| s.duration).sum(),
+            keys: sessions.iter().map(|s| s.typed + s.typo).sum(),
+            typo: sessions.iter().map(|s| s.typo).sum(),
+            avg_wpm: sessions.iter().map(|s| s.wpm).sum::<usize>() / count,
+            avg_acc: sessions.iter().map(|s| s.acc).sum::<usize>() / count,
+            best_wpm: sessions.iter().map(|s| s.wpm).max().unwrap_or(0),
+            best_acc: sessions.iter().map(|s| s.acc).max().unwrap_or(0),
+            trend: match (
+                Summary::average_wpm(&recent),
+                Summary::average_wpm(&previous),
+            ) {
+                (Some(r), Some(p)) => Some(r as i64 - p as i64),
+                _ => None,
+            },
+        }
+    }
+
+    fn average_wpm(sessions: &[&Session]) -> Option<usize> {
+        if sessions.is_empty() {
+            None
+        } else {
+            Some(sessions.iter().map(|s| s.wpm).sum::<usize>() / sessions.len())
+        }
+    }
+}
+
+impl Stats {
+    pub fn new(sessions: &[Session], now: u64) -> Self {
+        let mut languages: BTreeMap<String, Vec<&Session>> = BTreeMap::new();
+
+        // drills are built from lines of several files, of no language of their own
+        for session in sessions.iter().filter(|s| !s.drill) {
+            let language = if session.extension.is_empty() {
+                "-".to_owned()
+            } else {
+                session.extension.clone()
+            };
+            languages.entry(language).or_default().push(session);
+        }
+
+        let mut languages: Vec<(String, Summary)> = languages
+            .into_iter()
+            .map(|(language, sessions)| (language, Summary::new(&sessions, now)))
+            .collect();
+        languages.sort_by_key(|(_, summary)| Reverse(summary.sessions));
+
+        Stats {
+            total: Summary::new(&sessions.iter().collect::<Vec<&Session>>(), now),
+            languages,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.total.sessions == 0 {
+            return writeln!(f, "No sessions yet.");
+        }
+
+        writeln!(
+            f,
+            "sessions: {}  time: {}m{}s  keys: {}  typo: {}",
+            self.total.sessions,
+            self.total.duration / 60,
+            self.total.duration % 60,
+            self.total.keys,
+            self.total.typo
+        )?;
+        writeln!(
+            f,
+            "wpm: avg {} best {}  acc: avg {}% best {}%  trend: {}",
+            self.total.avg_wpm,
+            self.total.best_wpm,
+            self.total.avg_acc,
+            self.total.best_acc,
+            trend(self.total.trend)
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<10}{:>10}{:>10}{:>10}{:>10}{:>10}",
+            "lang", "sessions", "avg wpm", "best wpm", "avg acc", "trend"
+        )?;
+
+        for (language, summary) in &self.languages {
+            writeln!(
+                f,
+                "{:<10}{:>10}{:>10}{:>10}{:>10}{:>10}",
+                language,
+                summary.sessions,
+                summary.avg_wpm,
+                summary.best_wpm,
+                format!("{}%", summary.avg_acc),
+                trend(summary.trend)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn trend(trend: Option<i64>) -> String {
+    match trend {
+        Some(t) if t > 0 => format!("+{}", t),
+        Some(t) => t.to_string(),
+        None => "-".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn session(extension: &str, wpm: usize, acc: usize, timestamp: u64) -> Session {
+        Session {
+            path: PathBuf::from(format!("file.{}", extension)),
+            extension: extension.to_owned(),
+            duration: 30,
+            timestamp,
+            wpm,
+            acc,
+            typed: 90,
+            typo: 10,
+            wpm_plot: Vec::new(),
+            keystrokes: Vec::new(),
+            drill: false,
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let stats = Stats::new(&[], 0);
+        assert_eq!(stats.total.sessions, 0);
+        assert!(stats.languages.is_empty());
+        assert_eq!(stats.to_string(), "No sessions yet.\n");
+    }
+
+    #[test]
+    fn summary() {
+        let now = WEEK * 3;
+        let stats = Stats::new(
+            &[
+                session("rs", 40, 90, now - WEEK - 1),
+                session("rs", 60, 100, now - 1),
+                session("ts", 50, 80, now - WEEK * 2 - 1),
+            ],
+            now,
+        );
+
+        assert_eq!(stats.total.sessions, 3);
+        assert_eq!(stats.total.duration, 90);
+        assert_eq!(stats.total.keys, 300);
+        assert_eq!(stats.total.avg_wpm, 50);
+        assert_eq!(stats.total.best_wpm, 60);
+        assert_eq!(stats.total.best_acc, 100);
+        assert_eq!(stats.total.trend, Some(20));
+
+        let (language, rs) = &stats.languages[0];
+        assert_eq!(language, "rs");
+        assert_eq!(rs.sessions, 2);
+        assert_eq!(rs.avg_acc, 95);
+        assert_eq!(rs.trend, Some(20));
+
+        let (language, ts) = &stats.languages[1];
+        assert_eq!(language, "ts");
+        assert_eq!(ts.trend, None);
+    }
+
+    #[test]
+    fn drill() {
+        let stats = Stats::new(
+            &[
+                session("rs", 40, 90, 0),
+                Session {
+                    drill: true,
+                    ..session("rs", 60, 100, 0)
+                },
+            ],
+            0,
+        );
+
+        assert_eq!(stats.total.sessions, 2);
+        assert_eq!(stats.languages.len(), 1);
+        assert_eq!(stats.languages[0].1.sessions, 1);
+    }
+}
@@ -1,31 +1,68 @@
 use anyhow::{anyhow, Result};
-use clap::Parser;
+use clap::{parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ignore::Walk;
-use rand::prelude::*;
-use ratatui::{backend::CrosstermBackend, Terminal};
+use ignore::WalkBuilder;
+use rand::{rngs::StdRng, SeedableRng};
+use ratatui::{
+    backend::{CrosstermBackend, TestBackend},
+    Terminal,
+};
+use std::collections::HashSet;
+use std::ffi::OsStr;
 use std::io;
-use std::path::PathBuf;
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 mod app;
+mod config;
+mod driver;
+mod export;
+mod filter;
+mod git;
+mod history;
+mod pick;
+mod picker;
 mod reader;
+mod selection;
+#[cfg(test)]
+mod testing;
 mod types;
 mod views;
-use crate::views::{view, Theme};
-use app::App;
+use crate::views::{
+    highlight::Highlight,
+    keyboard::{Keyboard, Layout},
+    picker::picker_view,
+    theme::Theme,
+    Source,
+};
+use app::{App, Mode, TypingResult};
+use config::Config;
+use driver::{script::Script, Control, Driver};
+use export::{export, Format, Settings};
+use filter::{FileFilter, Limits, ATTRIBUTES_FILE, CONTENT_PEEK, MAX_FILE_SIZE, MAX_LINE_LENGTH};
+use history::{stats::Stats, History, Session};
+use pick::Strategy;
+use picker::Picker;
+use reader::archive::ArchiveReader;
+use reader::commit::CommitReader;
+use reader::drill::DrillReader;
 use reader::file::FileReader;
+use reader::rev::RevReader;
+use reader::snippet::Snippet;
+use reader::stdin::StdinReader;
 use reader::Reader;
-use types::typing::Typing;
+use selection::Selection;
+use types::clock::Clock;
 
-const QUIT_COMMAND: char = 'q';
-const EXIT_COMMAND: char = 'c';
-const RESTART_COMMAND: char = 'r';
-const ONE_SEC: Duration = Duration::from_secs(1);
+const DRILL_LINES: usize = 40;
+const FUNCTION_LINES: usize = 40;
+const PICK_DEPTH: usize = 100;
+const SCRIPT_WIDTH: u16 = 80;
+const SCRIPT_HEIGHT: u16 = 24;
 
 #[derive(Parser, Debug)]
 #[clap(author, about, long_about = None, version = "v0.1.0")]
@@ -36,6 +73,15 @@ struct Args {
     #[clap(long, default_value_t = 20)]
     line: usize,
 
+    #[clap(long, value_name = "N", conflicts_with = "lines", value_parser = count)]
+    words: Option<usize>,
+
+    #[clap(long, value_name = "N", value_parser = count)]
+    lines: Option<usize>,
+
+    #[clap(long, conflicts_with_all = &["words", "lines"])]
+    zen: bool,
+
     #[clap(short = 'f', parse(from_os_str), value_name = "file", value_hint = clap::ValueHint::FilePath)]
     file: Option<PathBuf>,
 
@@ -45,11 +91,96 @@ struct Args {
     #[clap(short = 'e', long)]
     extension: Option<String>,
 
-    #[clap(short = 't', default_value = "dark")]
+    #[clap(long, value_name = "language")]
+    lang: Vec<String>,
+
+    #[clap(long, value_name = "glob")]
+    include: Vec<String>,
+
+    #[clap(long, value_name = "glob")]
+    exclude: Vec<String>,
+
+    #[clap(short = 't', default_value = "dark", value_name = "theme")]
     theme: String,
 
+    #[clap(long, overrides_with = "no-force-correction")]
+    force_correction: bool,
+
+    #[clap(long, overrides_with = "force-correction")]
+    no_force_correction: bool,
+
+    #[clap(long, value_name = "rev")]
+    commit: Option<String>,
+
+    #[clap(long, value_name = "treeish", conflicts_with = "commit")]
+    rev: Option<String>,
+
     #[clap(long)]
-    force_correction: bool
+    drill: bool,
+
+    #[clap(short = 'i', long)]
+    interactive: bool,
+
+    #[clap(long, default_value = "random", possible_values = Strategy::NAMES)]
+    pick: String,
+
+    // --pick was given on the command line rather than in the config, so a file outside of a
+    // repository is an error instead of a uniform pick
+    #[clap(skip)]
+    explicit_pick: bool,
+
+    #[clap(long, default_value_t = PICK_DEPTH, value_name = "N")]
+    depth: usize,
+
+    #[clap(long, value_name = "N")]
+    seed: Option<u64>,
+
+    #[clap(long, value_name = "N")]
+    snippet: Option<usize>,
+
+    #[clap(long)]
+    function: bool,
+
+    #[clap(long, default_value_t = MAX_FILE_SIZE, value_name = "bytes")]
+    max_file_size: u64,
+
+    #[clap(long, default_value_t = MAX_LINE_LENGTH, value_name = "N")]
+    max_line_length: usize,
+
+    #[clap(long, parse(from_os_str), value_name = "path")]
+    output: Option<PathBuf>,
+
+    #[clap(long, default_value = "json", possible_values = Format::NAMES)]
+    format: String,
+
+    #[clap(long, default_value = "qwerty", possible_values = Layout::NAMES)]
+    layout: String,
+
+    #[clap(long, parse(from_os_str), value_name = "path")]
+    config: Option<PathBuf>,
+
+    #[clap(long, parse(from_os_str), value_name = "path")]
+    script: Option<PathBuf>,
+
+    #[clap(parse(from_os_str), value_name = "path", conflicts_with_all = &["commit", "rev"])]
+    paths: Vec<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Show statistics of finished sessions
+    Stats,
+}
+
+// Word and line counts of at least 1, as an empty text can not be typed.
+fn count(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("{} is not a positive number", value)),
+    }
 }
 
 fn close_app() -> Result<()> {
@@ -59,167 +190,580 @@ fn close_app() -> Result<()> {
     Ok(())
 }
 
-fn run_app(mut app: App, text: &str, theme: Theme, file: PathBuf) -> io::Result<()> {
+fn run_app(mut driver: Driver) -> io::Result<Vec<TypingResult>> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
-    let mut last_tick = Instant::now();
 
     loop {
-        terminal.draw(|f| view(f, &app, &theme, file.clone()))?;
-
-        let timeout = ONE_SEC
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
+        driver.draw(&mut terminal)?;
 
-        if crossterm::event::poll(timeout)? {
+        if crossterm::event::poll(driver.timeout())? {
             if let Event::Key(key) = event::read()? {
-                match app.typing {
-                    Typing::BeforeStart(_) => match key.code {
-                        KeyCode::Right => {
-                            app = app.next_time();
-                        }
-                        KeyCode::Left => {
-                            app = app.prev_time();
-                        }
-                        KeyCode::Char(QUIT_COMMAND) => {
-                            return Ok(());
-                        }
-                        KeyCode::Char(EXIT_COMMAND) if key.modifiers == KeyModifiers::CONTROL => {
-                            return Ok(());
-                        }
-                        KeyCode::Char(c) => {
-                            app = app.start().input(c);
-                        }
-                        _ => (),
-                    },
-                    Typing::Running(_) => match key.code {
-                        KeyCode::Enter => {
-                            app = app.input('\n');
-                        }
-                        KeyCode::Char(EXIT_COMMAND) if key.modifiers == KeyModifiers::CONTROL => {
-                            app = app.finish();
-                        }
-                        KeyCode::Char(c) => {
-                            app = app.input(c);
-                        }
-                        KeyCode::Backspace | KeyCode::Delete => {
-                            app = app.backspace();
-                        }
-                        _ => (),
-                    },
-                    Typing::Finish(_) => match key.code {
-                        KeyCode::Char(RESTART_COMMAND) => app = app.restart(text),
-                        KeyCode::Char(QUIT_COMMAND) => {
-                            return Ok(());
-                        }
-                        KeyCode::Char(EXIT_COMMAND) if key.modifiers == KeyModifiers::CONTROL => {
-                            return Ok(());
-                        }
-                        _ => (),
-                    },
+                if driver.handle(key) == Control::Quit {
+                    return Ok(driver.results());
                 }
             }
         }
 
-        if last_tick.elapsed() >= ONE_SEC {
-            if let Typing::Running(_) = app.typing {
-                app = app.tick();
-                last_tick = Instant::now();
-            }
-        }
+        driver.tick();
     }
 }
 
-fn start_typing(file: PathBuf, time: Duration, display_line: usize, theme: Theme) -> Result<()> {
-    let reader = FileReader::new(file.clone());
+fn start_typing(
+    reader: &dyn Reader,
+    file: PathBuf,
+    subject: Option<String>,
+    args: &Args,
+    config: &Config,
+) -> Result<()> {
     match reader.load() {
         Ok(text) => {
-            let app = App::new(&text, time, display_line)?;
-            let res = run_app(app, &text, theme, file);
+            let snippet = match (args.function, args.snippet) {
+                (true, lines) => Snippet::function(
+                    &text,
+                    &file,
+                    lines.unwrap_or(FUNCTION_LINES),
+                    &mut rng(args),
+                ),
+                (false, Some(lines)) => Snippet::pick(&text, lines, &mut rng(args)),
+                (false, None) => Snippet::whole(&text),
+            };
+            let text = snippet.text;
+            let app = App::new(
+                &text,
+                Duration::from_secs(args.time as u64),
+                args.line,
+                args.force_correction,
+            )?;
+            let app = app.with_mode(Mode::new(args.words, args.lines, args.zen));
+            let app = match args.script {
+                Some(_) => app.with_clock(Clock::manual()),
+                None => app,
+            };
+            let theme = Theme::new(&args.theme)?.with_colors(&config.colors)?;
+            let highlight = Highlight::new(&App::filter_text(&text), &file, theme.syntax());
+            let keyboard = Keyboard::new(Layout::new(&args.layout));
+            let mut driver = Driver::new(
+                app,
+                &text,
+                theme,
+                highlight,
+                keyboard,
+                Source {
+                    path: file.clone(),
+                    subject,
+                    first_line: (args.function || args.snippet.is_some())
+                        .then_some(snippet.first_line),
+                    seed: args.seed,
+                },
+                config.keys.clone(),
+            );
 
-            if let Err(err) = res {
-                return Err(anyhow!(format!("{:?}", err)));
-            }
+            if let Some(script) = &args.script {
+                let script = Script::load(script)?;
+                let mut terminal = Terminal::new(TestBackend::new(SCRIPT_WIDTH, SCRIPT_HEIGHT))?;
+                driver.run_script(&mut terminal, &script)?;
+                let results = driver.results();
 
-            close_app()?;
-            Ok(())
-        }
-        Err(_) => Err(anyhow!(format!("Failed to load file."))),
-    }
-}
-
-fn list_files(path: PathBuf, target_extension: Option<String>) -> Vec<PathBuf> {
-    Walk::new(path)
-        .filter_map(|e| match e {
-            Ok(entry) => {
-                let extension = entry
-                    .path()
-                    .extension()
-                    .and_then(|f| f.to_str())
-                    .unwrap_or("")
-                    .to_lowercase();
-                let match_extension = |target: Option<String>, e: String| {
-                    target.map(|t| e == t.to_lowercase()).unwrap_or(true)
+                return match &args.output {
+                    Some(output) => export(
+                        output,
+                        &Format::new(&args.format),
+                        &file,
+                        &settings(args),
+                        &results,
+                    ),
+                    None => {
+                        println!("{}", serde_json::to_string_pretty(&results)?);
+                        Ok(())
+                    }
                 };
+            }
 
-                if entry.file_type().unwrap().is_file()
-                    && !extension.is_empty()
-                    && match_extension(target_extension.clone(), extension.to_string().clone())
-                {
-                    Some(entry)
-                } else {
-                    None
+            match run_app(driver) {
+                Ok(results) => {
+                    close_app()?;
+
+                    if let Some(output) = &args.output {
+                        export(
+                            output,
+                            &Format::new(&args.format),
+                            &file,
+                            &settings(args),
+                            &results,
+                        )?;
+                    }
+
+                    History::open()?.save(
+                        &results
+                            .iter()
+                            .map(|r| Session::new(r, &file, args.drill))
+                            .collect::<Vec<Session>>(),
+                    )
                 }
+                Err(err) => Err(anyhow!(format!("{:?}", err))),
             }
-            _ => None,
+        }
+        Err(err) => Err(err.context(format!("Failed to load {}.", file.display()))),
+    }
+}
+
+fn settings(args: &Args) -> Settings {
+    Settings {
+        time: args.time,
+        line: args.line,
+        words: args.words,
+        lines: args.lines,
+        zen: args.zen,
+        force_correction: args.force_correction,
+        extension: args.extension.clone(),
+        lang: args.lang.clone(),
+        include: args.include.clone(),
+        exclude: args.exclude.clone(),
+        theme: args.theme.clone(),
+        layout: args.layout.clone(),
+        commit: args.commit.clone(),
+        rev: args.rev.clone(),
+        drill: args.drill,
+        snippet: args.snippet,
+        function: args.function,
+        pick: args.pick.clone(),
+        seed: args.seed,
+    }
+}
+
+// Files and snippets are chosen with the seed of the session, so that it can be reproduced.
+fn rng(args: &Args) -> StdRng {
+    StdRng::seed_from_u64(args.seed.unwrap_or_default())
+}
+
+fn selection(args: &Args, root: &Path) -> Result<Selection> {
+    Selection::new(
+        root,
+        &args.extension.iter().cloned().collect::<Vec<String>>(),
+        &args.lang,
+        &args.include,
+        &args.exclude,
+    )
+}
+
+fn limits(args: &Args) -> Limits {
+    Limits {
+        max_file_size: args.max_file_size,
+        max_line_length: args.max_line_length,
+    }
+}
+
+fn list_files(path: PathBuf, selection: &Selection, limits: Limits) -> Vec<PathBuf> {
+    let mut filter = FileFilter::new(&path, limits);
+    let mut files = Vec::new();
+
+    // sorted so that a seed picks the same file on every machine
+    for entry in WalkBuilder::new(&path)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .filter_map(|e| e.ok())
+    {
+        let relative = entry.path().strip_prefix(&path).unwrap_or(entry.path());
+
+        match entry.file_type() {
+            // a directory comes before its files, with the .gitattributes they are matched by
+            Some(t) if t.is_dir() => filter.add_attributes(relative),
+            Some(t) if t.is_file() && selection.matches(relative) && filter.accepts(relative) => {
+                files.push(entry.into_path())
+            }
+            _ => (),
+        }
+    }
+    files
+}
+
+// Files in the tree of a revision, filtered like the files of a directory.
+fn list_rev_files(dir: &Path, rev: &str, args: &Args) -> Result<Vec<PathBuf>> {
+    let selection = selection(args, dir)?;
+    let filter = FileFilter::new(dir, limits(args));
+    let blobs: Vec<_> = RevReader::list_files(dir, rev)?
+        .into_iter()
+        .filter(|b| {
+            selection.matches(&b.path)
+                && filter.accepts_path(&b.path)
+                && filter.accepts_size(b.size)
         })
-        .map(|e| e.into_path())
-        .collect()
+        .collect();
+    let contents = RevReader::contents(dir, &blobs, CONTENT_PEEK)?;
+
+    Ok(blobs
+        .into_iter()
+        .zip(contents)
+        .filter(|(_, content)| filter.accepts_prefix(content))
+        .map(|(blob, _)| blob.path)
+        .collect())
 }
 
-fn pick_file(path: PathBuf, target_extension: Option<String>) -> Option<PathBuf> {
-    let files = list_files(path, target_extension);
+// Files in an archive, filtered like the files of a directory.
+fn list_archive_files(archive: &Path, args: &Args) -> Result<Vec<PathBuf>> {
+    let selection = selection(args, archive)?;
+    let entries = ArchiveReader::list_files(archive)?;
+    let mut filter = FileFilter::without_attributes(archive, limits(args));
+
+    // the .gitattributes of the archive, not of the directory it is in, parents first
+    let mut attributes: Vec<_> = entries
+        .iter()
+        .filter(|e| e.path.file_name() == Some(OsStr::new(ATTRIBUTES_FILE)))
+        .cloned()
+        .collect();
+    attributes.sort_by_key(|e| e.path.components().count());
+    for (entry, text) in
+        attributes
+            .iter()
+            .zip(ArchiveReader::contents(archive, &attributes, CONTENT_PEEK)?)
+    {
+        filter.add_attributes_text(
+            entry.path.parent().unwrap_or(Path::new("")),
+            &String::from_utf8_lossy(&text),
+        );
+    }
+
+    let entries: Vec<_> = entries
+        .into_iter()
+        .filter(|e| {
+            selection.matches(&e.path)
+                && filter.accepts_path(&e.path)
+                && filter.accepts_size(e.size)
+        })
+        .collect();
+    let contents = ArchiveReader::contents(archive, &entries, CONTENT_PEEK)?;
+
+    Ok(entries
+        .into_iter()
+        .zip(contents)
+        .filter(|(_, content)| filter.accepts_prefix(content))
+        .map(|(entry, _)| entry.path)
+        .collect())
+}
+
+fn run_picker(picker: &mut Picker, theme: &Theme) -> Result<picker::Control> {
+    enable_raw_mode()?;
 
+    // the terminal is restored on errors too
+    let control = (|| -> Result<picker::Control> {
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        let backend = CrosstermBackend::new(stdout);
+        let mut terminal = Terminal::new(backend)?;
+
+        loop {
+            terminal.draw(|f| picker_view(f, picker, theme))?;
+
+            if let Event::Key(key) = event::read()? {
+                match picker.handle(key) {
+                    picker::Control::Continue => (),
+                    control => return Ok(control),
+                }
+            }
+        }
+    })();
+
+    close_app()?;
+    control
+}
+
+// Lets the user search the files with --interactive, otherwise picks one at random.
+// None means the picker was closed without choosing a file.
+// `root` is what the files are relative to, for --pick to find them in the history, and None
+// for files without a history, like those of an archive.
+fn choose_file(
+    files: Vec<PathBuf>,
+    root: Option<&Path>,
+    load: impl Fn(&Path) -> Result<String> + 'static,
+    args: &Args,
+    config: &Config,
+) -> Result<Option<PathBuf>> {
     if files.is_empty() {
-        return None;
+        return Err(anyhow!(format!("File not found.")));
+    }
+    let strategy = Strategy::new(&args.pick);
+    let weights = match root {
+        Some(root) => strategy
+            .weights(
+                &files.iter().map(|f| root.join(f)).collect::<Vec<PathBuf>>(),
+                args.depth,
+            )?
+            .map(|weights| {
+                files
+                    .iter()
+                    .map(|f| (f.clone(), weights[&root.join(f)]))
+                    .collect()
+            }),
+        None => None,
+    };
+    if weights.is_none() && strategy != Strategy::Random && args.explicit_pick {
+        return Err(anyhow!(
+            "--pick {} needs files in a git repository.",
+            args.pick
+        ));
     }
+    let mut rng = rng(args);
 
-    let mut rng = rand::thread_rng();
-    let file_index = rng.gen_range(0..files.len());
-    let file = &files[file_index];
-    Some(file.clone())
+    if !args.interactive {
+        return Ok(pick::pick(&files, weights.as_ref(), &mut rng));
+    }
+
+    let theme = Theme::new(&args.theme)?.with_colors(&config.colors)?;
+    let mut picker = Picker::new(files, load);
+
+    Ok(match run_picker(&mut picker, &theme)? {
+        picker::Control::Pick(file) => Some(file),
+        picker::Control::Random => {
+            let matches: Vec<PathBuf> = picker.matches().iter().map(|m| m.path.clone()).collect();
+            pick::pick(&matches, weights.as_ref(), &mut rng)
+        }
+        _ => None,
+    })
+}
+
+// Files and directories given on the command line, or the current directory.
+fn paths(args: &Args) -> Vec<PathBuf> {
+    let paths: Vec<PathBuf> = args
+        .paths
+        .iter()
+        .chain(&args.file)
+        .chain(&args.dir)
+        .cloned()
+        .collect();
+
+    if paths.is_empty() {
+        vec![PathBuf::from(r".")]
+    } else {
+        paths
+    }
+}
+
+// Named files are used as they are and directories are walked, all into one pool.
+fn pool_files(paths: &[PathBuf], args: &Args) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        if path.is_dir() {
+            files.extend(list_files(
+                path.clone(),
+                &selection(args, path)?,
+                limits(args),
+            ));
+        } else if path.is_file() {
+            files.push(path.clone());
+        } else {
+            return Err(anyhow!(format!("{} not found.", path.display())));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    files.retain(|f| seen.insert(f.clone()));
+    Ok(files)
+}
+
+// A name for text that is not read from a file, with the first selected extension
+// for highlighting.
+fn text_path(name: &str, args: &Args) -> Result<PathBuf> {
+    Ok(
+        match selection(args, Path::new(r"."))?.extensions().first() {
+            Some(extension) => PathBuf::from(format!("{}.{}", name, extension)),
+            None => PathBuf::from(name),
+        },
+    )
+}
+
+fn start_drill(args: &Args, config: &Config) -> Result<()> {
+    let files = pool_files(&paths(args), args)?;
+    let reader = DrillReader::new(files, &History::open()?.key_stats()?, DRILL_LINES);
+
+    start_typing(&reader, text_path("drill", args)?, None, args, config)
+}
+
+fn show_stats() -> Result<()> {
+    let sessions = History::open()?.load()?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    print!("{}", Stats::new(&sessions, now));
+    Ok(())
+}
+
+// Values in the config file are used unless the option is given on the command line.
+fn merge_config(mut args: Args, matches: &ArgMatches, config: &Config) -> Args {
+    let is_default = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+
+    if let (true, Some(time)) = (is_default("time"), config.time) {
+        args.time = time;
+    }
+    if let (true, Some(line)) = (is_default("line"), config.line) {
+        args.line = line;
+    }
+    if let (true, Some(theme)) = (is_default("theme"), &config.theme) {
+        args.theme = theme.clone();
+    }
+    if let (true, Some(layout)) = (is_default("layout"), &config.layout) {
+        args.layout = layout.clone();
+    }
+    if let (true, Some(format)) = (is_default("format"), &config.format) {
+        args.format = format.clone();
+    }
+    if let (true, Some(size)) = (is_default("max-file-size"), config.max_file_size) {
+        args.max_file_size = size;
+    }
+    if let (true, Some(pick)) = (is_default("pick"), &config.pick) {
+        args.pick = pick.clone();
+    }
+    args.explicit_pick = !is_default("pick");
+    if let (true, Some(length)) = (is_default("max-line-length"), config.max_line_length) {
+        args.max_line_length = length;
+    }
+    args.extension = args.extension.or_else(|| config.extension.clone());
+    args.force_correction = !args.no_force_correction
+        && (args.force_correction || config.force_correction.unwrap_or(false));
+    args
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches)?;
+    let config = Config::load(args.config.as_deref())?;
+    let mut args = merge_config(args, &matches, &config);
+    args.seed.get_or_insert_with(rand::random);
 
-    match (args.file, args.dir) {
-        (Some(file), _) => start_typing(
-            file.clone(),
-            Duration::from_secs(args.time as u64),
-            args.line,
-            Theme::new(&args.theme),
-        ),
-        (_, Some(dir)) => match pick_file(dir, args.extension) {
+    if let Some(Command::Stats) = args.command {
+        return show_stats();
+    }
+
+    if args.drill {
+        return start_drill(&args, &config);
+    }
+
+    if let Some(rev) = args.commit.clone() {
+        let dir = args.dir.clone().unwrap_or_else(|| PathBuf::from(r"."));
+        let file = match args.file.clone() {
+            Some(file) => Some(file),
+            None => {
+                let (root, rev) = (dir.clone(), rev.clone());
+                let selection = selection(&args, &dir)?;
+                let filter = FileFilter::new(&dir, limits(&args));
+                choose_file(
+                    CommitReader::list_files(&dir, &rev)?
+                        .into_iter()
+                        .filter(|f| selection.matches(f) && filter.accepts_path(f))
+                        .collect(),
+                    Some(&dir),
+                    move |f| CommitReader::new(root.clone(), rev.clone(), f.to_path_buf()).load(),
+                    &args,
+                    &config,
+                )?
+            }
+        };
+
+        return match file {
+            Some(file) => {
+                let reader = CommitReader::new(dir.clone(), rev, file.clone());
+                start_typing(
+                    &reader,
+                    dir.join(file),
+                    reader.subject().ok().filter(|s| !s.is_empty()),
+                    &args,
+                    &config,
+                )
+            }
+            None => Ok(()),
+        };
+    }
+
+    if let Some(rev) = args.rev.clone() {
+        let dir = args.dir.clone().unwrap_or_else(|| PathBuf::from(r"."));
+        let file = match args.file.clone() {
+            Some(file) => Some(file),
+            None => {
+                let (root, rev) = (dir.clone(), rev.clone());
+                choose_file(
+                    list_rev_files(&dir, &rev, &args)?,
+                    Some(&dir),
+                    move |f| RevReader::new(root.clone(), rev.clone(), f.to_path_buf()).load(),
+                    &args,
+                    &config,
+                )?
+            }
+        };
+
+        return match file {
             Some(file) => start_typing(
-                file.clone(),
-                Duration::from_secs(args.time as u64),
-                args.line,
-                Theme::new(&args.theme),
+                &RevReader::new(dir.clone(), rev, file.clone()),
+                dir.join(file),
+                None,
+                &args,
+                &config,
             ),
-            None => Err(anyhow!(format!("File not found."))),
-        },
-        _ => match pick_file(PathBuf::from(r"."), args.extension) {
+            None => Ok(()),
+        };
+    }
+
+    if let Some(archive) = args.dir.clone().filter(|d| ArchiveReader::is_archive(d)) {
+        let file = match args.file.clone() {
+            Some(file) => Some(file),
+            None => {
+                let root = archive.clone();
+                choose_file(
+                    list_archive_files(&archive, &args)?,
+                    None,
+                    move |f| ArchiveReader::new(root.clone(), f.to_path_buf()).load(),
+                    &args,
+                    &config,
+                )?
+            }
+        };
+
+        return match file {
             Some(file) => start_typing(
-                file.clone(),
-                Duration::from_secs(args.time as u64),
-                args.line,
-                Theme::new(&args.theme),
+                &ArchiveReader::new(archive.clone(), file.clone()),
+                archive.join(file),
+                None,
+                &args,
+                &config,
             ),
-            None => Err(anyhow!(format!("File not found."))),
+            None => Ok(()),
+        };
+    }
+
+    let paths = paths(&args);
+    let stdin = Path::new("-");
+
+    match paths.as_slice() {
+        [path] if path == stdin => start_typing(
+            &StdinReader,
+            text_path("stdin", &args)?,
+            None,
+            &args,
+            &config,
+        ),
+        _ if paths.iter().any(|p| p == stdin) => {
+            Err(anyhow!(format!("- can not be combined with other paths.")))
+        }
+        [file] if file.is_file() => start_typing(
+            &FileReader::new(file.clone()),
+            file.clone(),
+            None,
+            &args,
+            &config,
+        ),
+        _ => match choose_file(
+            pool_files(&paths, &args)?,
+            Some(Path::new("")),
+            |f| FileReader::new(f.to_path_buf()).load(),
+            &args,
+            &config,
+        )? {
+            Some(file) => start_typing(&FileReader::new(file.clone()), file, None, &args, &config),
+            None => Ok(()),
         },
     }
 }
@@ -0,0 +1,251 @@
+use crate::git::git;
+use anyhow::{anyhow, Result};
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use std::collections::HashMap;
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+// How a file is picked at random, weighted by the history of the repository.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Strategy {
+    Random,
+    Recent, // files touched in the last commits
+    Churn,  // by the number of commits touching them
+    Mine,   // by the number of commits of the configured author touching them
+}
+
+impl Strategy {
+    pub const NAMES: [&'static str; 4] = ["random", "recent", "churn", "mine"];
+
+    pub fn new(name: &str) -> Self {
+        match name {
+            "recent" => Strategy::Recent,
+            "churn" => Strategy::Churn,
+            "mine" => Strategy::Mine,
+            _ => Strategy::Random,
+        }
+    }
+
+    // Weights of the files from the last `depth` commits of the repository they are in,
+    // None when every file is as likely or the files are not in a repository.
+    pub fn weights(
+        &self,
+        files: &[PathBuf],
+        depth: usize,
+    ) -> Result<Option<HashMap<PathBuf, f64>>> {
+        let dir = match files.first() {
+            Some(file) if *self != Strategy::Random => canonical(file)
+                .parent()
+                .map(|p| p.to_path_buf())
+                .unwrap_or_default(),
+            _ => return Ok(None),
+        };
+        let toplevel = match git(&dir, &["rev-parse", "--show-toplevel"]) {
+            Ok(toplevel) => PathBuf::from(toplevel.trim()),
+            Err(_) => return Ok(None),
+        };
+        let depth = depth.to_string();
+        let mut args = vec!["log", "-n", &depth, "--format=%x00", "--name-only"];
+        let author;
+
+        if *self == Strategy::Mine {
+            let email = git(&toplevel, &["config", "user.email"])
+                .map_err(|_| anyhow!("user.email is not set in the git config."))?;
+            author = format!("--author=<{}>", email.trim());
+            args.push(&author);
+        }
+
+        let counts = Strategy::counts(&git(&toplevel, &args)?);
+        Ok(Some(self.weigh(files, &toplevel, &counts)))
+    }
+
+    // Commits touching each file, from `git log --format=%x00 --name-only`.
+    fn counts(log: &str) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+
+        for commit in log.split('\0') {
+            for file in commit.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+                *counts.entry(file.to_owned()).or_insert(0) += 1;
+            }
+        }
+
+        counts
+    }
+
+    fn weigh(
+        &self,
+        files: &[PathBuf],
+        toplevel: &Path,
+        counts: &HashMap<String, usize>,
+    ) -> HashMap<PathBuf, f64> {
+        let toplevel = canonical(toplevel);
+
+        files
+            .iter()
+            .map(|file| {
+                let count = canonical(file)
+                    .strip_prefix(&toplevel)
+                    .ok()
+                    .and_then(|p| counts.get(&p.to_string_lossy().replace('\\', "/")))
+                    .copied()
+                    .unwrap_or(0);
+                let weight = match self {
+                    Strategy::Recent => count.min(1) as f64,
+                    _ => count as f64,
+                };
+                (file.clone(), weight)
+            })
+            .collect()
+    }
+}
+
+// Picks one of the files, by their weights if any of them has one.
+pub fn pick<R: Rng>(
+    files: &[PathBuf],
+    weights: Option<&HashMap<PathBuf, f64>>,
+    rng: &mut R,
+) -> Option<PathBuf> {
+    if files.is_empty() {
+        return None;
+    }
+
+    let weighted = weights.and_then(|weights| {
+        WeightedIndex::new(files.iter().map(|f| weights.get(f).copied().unwrap_or(0.0))).ok()
+    });
+
+    let index = match weighted {
+        Some(weighted) => weighted.sample(rng),
+        None => rng.gen_range(0..files.len()),
+    };
+    Some(files[index].clone())
+}
+
+// The path with symlinks resolved like in the toplevel given by git, as far as it exists.
+fn canonical(path: &Path) -> PathBuf {
+    let path = absolute(path);
+
+    path.ancestors()
+        .find_map(|dir| {
+            let canonical = dir.canonicalize().ok()?;
+            match path.strip_prefix(dir) {
+                Ok(rest) if !rest.as_os_str().is_empty() => Some(canonical.join(rest)),
+                _ => Some(canonical),
+            }
+        })
+        .unwrap_or(path)
+}
+
+// The absolute path without `.` and `..`, without touching the file system since the file
+// may only exist at another revision.
+fn absolute(path: &Path) -> PathBuf {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir().unwrap_or_default().join(path)
+    };
+
+    path.components().fold(PathBuf::new(), |mut path, c| {
+        match c {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                path.pop();
+            }
+            c => path.push(c),
+        }
+        path
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn counts() {
+        let log = "\x00\n\nsrc/main.rs\nsrc/app.rs\n\x00\n\nsrc/main.rs\n";
+        let counts = Strategy::counts(log);
+        assert_eq!(counts.get("src/main.rs"), Some(&2));
+        assert_eq!(counts.get("src/app.rs"), Some(&1));
+        assert_eq!(counts.len(), 2);
+    }
+
+    #[test]
+    fn weigh() {
+        let counts = Strategy::counts("\x00\n\nsrc/main.rs\nsrc/app.rs\n\x00\n\nsrc/main.rs\n");
+        let files = vec![
+            PathBuf::from("/repo/src/./main.rs"),
+            PathBuf::from("/repo/src/views/../app.rs"),
+            PathBuf::from("/repo/README.md"),
+        ];
+        let toplevel = Path::new("/repo");
+
+        let churn = Strategy::Churn.weigh(&files, toplevel, &counts);
+        assert_eq!(churn[&files[0]], 2.0);
+        assert_eq!(churn[&files[1]], 1.0);
+        assert_eq!(churn[&files[2]], 0.0);
+
+        let recent = Strategy::Recent.weigh(&files, toplevel, &counts);
+        assert_eq!(recent[&files[0]], 1.0);
+    }
+
+    #[test]
+    fn pick_weighted() {
+        let files = vec![PathBuf::from("a.rs"), PathBuf::from("b.rs")];
+        let mut rng = StdRng::seed_from_u64(1);
+        let weights: HashMap<PathBuf, f64> = vec![(files[0].clone(), 0.0), (files[1].clone(), 3.0)]
+            .into_iter()
+            .collect();
+
+        for _ in 0..10 {
+            assert_eq!(
+                pick(&files, Some(&weights), &mut rng),
+                Some(files[1].clone())
+            );
+        }
+
+        // no weights at all: uniform
+        let none: HashMap<PathBuf, f64> = HashMap::new();
+        assert!(pick(&files, Some(&none), &mut rng).is_some());
+        assert!(pick(&files, None, &mut rng).is_some());
+        assert_eq!(pick(&[], None, &mut rng), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn canonical() {
+        let temp = TempDir::new("pick-canonical");
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("repo/src")).unwrap();
+        std::os::unix::fs::symlink(dir.join("repo"), dir.join("link")).unwrap();
+        let repo = dir.canonicalize().unwrap().join("repo");
+
+        assert_eq!(super::canonical(&dir.join("link/src")), repo.join("src"));
+        assert_eq!(
+            super::canonical(&dir.join("link/src/deleted.rs")),
+            repo.join("src/deleted.rs")
+        );
+    }
+
+    #[test]
+    fn outside_repository() {
+        assert_eq!(
+            Strategy::Churn
+                .weights(&[PathBuf::from("/nonexistent/a.rs")], 10)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn random() {
+        assert_eq!(
+            Strategy::Random
+                .weights(&[PathBuf::from("a.rs")], 10)
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,260 @@
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::{Path, PathBuf};
+use syntect::parsing::SyntaxSet;
+
+const PREVIEW_LINES: usize = 200;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Control {
+    Continue,
+    Pick(PathBuf),
+    Random,
+    Cancel,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match {
+    pub path: PathBuf,
+    pub label: String,
+    pub indices: Vec<usize>, // matched character positions in label
+    score: i64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Preview {
+    pub text: String,
+    pub lines: usize,
+    pub language: String,
+}
+
+type Load = Box<dyn Fn(&Path) -> Result<String>>;
+
+// Fuzzy search over a list of files, with a preview of the selected one.
+pub struct Picker {
+    files: Vec<PathBuf>,
+    load: Load,
+    query: String,
+    matches: Vec<Match>,
+    selected: usize,
+    preview: Option<Preview>,
+    syntax_set: SyntaxSet,
+}
+
+impl Picker {
+    // `load` reads the text of a file for the preview, the same way the session will.
+    pub fn new(files: Vec<PathBuf>, load: impl Fn(&Path) -> Result<String> + 'static) -> Self {
+        let mut picker = Picker {
+            files,
+            load: Box::new(load),
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+            preview: None,
+            syntax_set: SyntaxSet::load_defaults_newlines(),
+        };
+        picker.search();
+        picker
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn matches(&self) -> &[Match] {
+        &self.matches
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn total(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn preview(&self) -> Option<&Preview> {
+        self.preview.as_ref()
+    }
+
+    pub fn handle(&mut self, key: KeyEvent) -> Control {
+        let ctrl = key.modifiers == KeyModifiers::CONTROL;
+
+        match key.code {
+            KeyCode::Esc => return Control::Cancel,
+            KeyCode::Char('c') if ctrl => return Control::Cancel,
+            KeyCode::Char('r') if ctrl => return Control::Random,
+            KeyCode::Enter => {
+                return match self.matches.get(self.selected) {
+                    Some(m) => Control::Pick(m.path.clone()),
+                    None => Control::Continue,
+                }
+            }
+            KeyCode::Up => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Char('p') if ctrl => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Down => self.select(self.selected + 1),
+            KeyCode::Char('n') if ctrl => self.select(self.selected + 1),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.search();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.search();
+            }
+            _ => (),
+        }
+
+        Control::Continue
+    }
+
+    fn search(&mut self) {
+        let mut matches: Vec<Match> = self
+            .files
+            .iter()
+            .filter_map(|path| {
+                let label = path.to_string_lossy().to_string();
+                fuzzy_match(&self.query, &label).map(|(score, indices)| Match {
+                    path: path.clone(),
+                    label,
+                    indices,
+                    score,
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.label.cmp(&b.label)));
+
+        self.matches = matches;
+        self.preview = None;
+        self.select(0);
+    }
+
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.matches.len().saturating_sub(1));
+
+        if self.preview.is_none() || index != self.selected {
+            self.selected = index;
+            self.preview = self.matches.get(index).map(|m| self.load_preview(&m.path));
+        }
+    }
+
+    fn load_preview(&self, path: &Path) -> Preview {
+        let text = (self.load)(path).unwrap_or_default();
+        let language = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(|e| self.syntax_set.find_syntax_by_extension(e))
+            .map(|s| s.name.clone())
+            .unwrap_or_else(|| "Plain Text".to_owned());
+
+        Preview {
+            text: text
+                .lines()
+                .take(PREVIEW_LINES)
+                .collect::<Vec<&str>>()
+                .join("\n")
+                .replace('\t', "    "),
+            lines: text.lines().count(),
+            language,
+        }
+    }
+}
+
+// Matches the query as a subsequence of the text, case-insensitively unless the query has
+// upper case letters. Consecutive characters and the starts of words score higher.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let case_sensitive = query.chars().any(|c| c.is_uppercase());
+    let normalize = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_ascii_lowercase()
+        }
+    };
+    let chars: Vec<char> = text.chars().collect();
+    let mut indices = Vec::new();
+    let mut score = 0;
+    let mut start = 0;
+
+    for q in query.chars().map(normalize) {
+        let i = (start..chars.len()).find(|i| normalize(chars[*i]) == q)?;
+        let boundary = i == 0 || matches!(chars[i - 1], '/' | '\\' | '_' | '-' | '.' | ' ');
+
+        score += 1;
+        if indices.last().is_some_and(|last| last + 1 == i) {
+            score += 5;
+        }
+        if boundary {
+            score += 3;
+        }
+        score -= (i - start) as i64 / 4;
+
+        indices.push(i);
+        start = i + 1;
+    }
+
+    Some((score * 100 - chars.len() as i64, indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    #[test]
+    fn fuzzy() {
+        assert_eq!(fuzzy_match("", "src/main.rs").map(|m| m.1), Some(vec![]));
+        assert_eq!(
+            fuzzy_match("smr", "src/main.rs").map(|m| m.1),
+            Some(vec![0, 4, 9])
+        );
+        assert!(fuzzy_match("msr", "src/main.rs").is_none());
+        assert!(fuzzy_match("Main", "src/main.rs").is_none());
+
+        let score = |q: &str, t: &str| fuzzy_match(q, t).unwrap().0;
+        assert!(score("main", "src/main.rs") > score("main", "src/m_a_i_n.rs"));
+        assert!(score("app", "src/app.rs") > score("app", "src/views/app.rs"));
+    }
+
+    #[test]
+    fn handle() {
+        let files = vec![
+            PathBuf::from("src/app.rs"),
+            PathBuf::from("src/main.rs"),
+            PathBuf::from("Cargo.toml"),
+        ];
+        let mut picker = Picker::new(files, |p| {
+            Ok(format!("// {}\nfn main() {{}}\n", p.display()))
+        });
+        assert_eq!(picker.matches().len(), 3);
+        assert_eq!(picker.matches()[0].label, "Cargo.toml");
+
+        for c in "ma".chars() {
+            picker.handle(key(KeyCode::Char(c)));
+        }
+        assert_eq!(picker.query(), "ma");
+        assert_eq!(picker.matches()[0].label, "src/main.rs");
+        assert_eq!(picker.matches().len(), 1);
+
+        let preview = picker.preview().unwrap();
+        assert_eq!(preview.language, "Rust");
+        assert_eq!(preview.lines, 2);
+
+        picker.handle(key(KeyCode::Backspace));
+        picker.handle(key(KeyCode::Down));
+        picker.handle(key(KeyCode::Down));
+        picker.handle(key(KeyCode::Down));
+        assert_eq!(picker.selected(), 1);
+        assert_eq!(
+            picker.handle(key(KeyCode::Enter)),
+            Control::Pick(picker.matches()[1].path.clone())
+        );
+
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        assert_eq!(picker.handle(ctrl('r')), Control::Random);
+        assert_eq!(picker.handle(ctrl('c')), Control::Cancel);
+    }
+}
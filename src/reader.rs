@@ -1,4 +1,10 @@
+pub mod archive;
+pub mod commit;
+pub mod drill;
 pub mod file;
+pub mod rev;
+pub mod snippet;
+pub mod stdin;
 
 use anyhow::Result;
 
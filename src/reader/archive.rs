@@ -0,0 +1,289 @@
+use crate::reader::Reader;
+use anyhow::{anyhow, Result};
+use flate2::read::GzDecoder;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub path: PathBuf, // relative to the root of the archive
+    pub size: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl Kind {
+    fn new(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+
+        if name.ends_with(".zip") {
+            Some(Kind::Zip)
+        } else if name.ends_with(".tar") {
+            Some(Kind::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Kind::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+// Reads a file inside a zip, tar or tar.gz archive.
+pub struct ArchiveReader {
+    archive: PathBuf,
+    path: PathBuf,
+}
+
+impl ArchiveReader {
+    pub fn new(archive: PathBuf, path: PathBuf) -> Self {
+        ArchiveReader { archive, path }
+    }
+
+    pub fn is_archive(path: &Path) -> bool {
+        path.is_file() && Kind::new(path).is_some()
+    }
+
+    // Regular files in the archive, without directories and links.
+    pub fn list_files(archive: &Path) -> Result<Vec<Entry>> {
+        let mut entries = Vec::new();
+
+        ArchiveReader::walk(archive, |path, size, _| {
+            entries.push(Entry { path, size });
+            Ok(false)
+        })?;
+        Ok(entries)
+    }
+
+    // The first `limit` bytes of the entries, read in one pass over the archive.
+    pub fn contents(archive: &Path, entries: &[Entry], limit: u64) -> Result<Vec<Vec<u8>>> {
+        let mut contents: HashMap<PathBuf, Vec<u8>> = HashMap::new();
+        let paths: HashSet<&Path> = entries.iter().map(|e| e.path.as_path()).collect();
+
+        if !entries.is_empty() {
+            ArchiveReader::walk(archive, |path, _, content| {
+                if paths.contains(path.as_path()) {
+                    let mut bytes = Vec::new();
+                    content.take(limit).read_to_end(&mut bytes)?;
+                    contents.insert(path, bytes);
+                }
+                Ok(contents.len() == paths.len())
+            })?;
+        }
+
+        entries
+            .iter()
+            .map(|e| {
+                contents
+                    .remove(&e.path)
+                    .ok_or_else(|| anyhow!("{} not found in the archive.", e.path.display()))
+            })
+            .collect()
+    }
+
+    // Calls `visit` with the path, size and content of each regular file until it returns true.
+    fn walk(
+        archive: &Path,
+        mut visit: impl FnMut(PathBuf, u64, &mut dyn Read) -> Result<bool>,
+    ) -> Result<()> {
+        let kind = Kind::new(archive)
+            .ok_or_else(|| anyhow!("{} is not an archive.", archive.display()))?;
+        let file = BufReader::new(File::open(archive)?);
+
+        match kind {
+            Kind::Zip => {
+                let mut zip = zip::ZipArchive::new(file)?;
+
+                for i in 0..zip.len() {
+                    let mut entry = zip.by_index(i)?;
+                    if !entry.is_file() || entry.is_symlink() {
+                        continue;
+                    }
+                    if let Some(path) = entry.enclosed_name().and_then(|p| normalize(&p)) {
+                        let size = entry.size();
+                        if visit(path, size, &mut entry)? {
+                            break;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Kind::Tar => ArchiveReader::walk_tar(file, visit),
+            Kind::TarGz => ArchiveReader::walk_tar(GzDecoder::new(file), visit),
+        }
+    }
+
+    fn walk_tar(
+        reader: impl Read,
+        mut visit: impl FnMut(PathBuf, u64, &mut dyn Read) -> Result<bool>,
+    ) -> Result<()> {
+        let mut tar = tar::Archive::new(reader);
+
+        for entry in tar.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            if let Some(path) = normalize(&entry.path()?) {
+                let size = entry.size();
+                if visit(path, size, &mut entry)? {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Reader for ArchiveReader {
+    fn load(&self) -> Result<String> {
+        let mut text = None;
+
+        ArchiveReader::walk(&self.archive, |path, _, content| {
+            if path != self.path {
+                return Ok(false);
+            }
+            let mut bytes = Vec::new();
+            content.read_to_end(&mut bytes)?;
+            text = Some(bytes);
+            Ok(true)
+        })?;
+
+        match text {
+            Some(bytes) => String::from_utf8(bytes)
+                .map_err(|_| anyhow!("{} is not a text file.", self.path.display())),
+            None => Err(anyhow!(
+                "{} not found in {}.",
+                self.path.display(),
+                self.archive.display()
+            )),
+        }
+    }
+}
+
+// `./src/main.rs` as `src/main.rs`, and nothing for paths outside of the archive.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::Normal(c) => normalized.push(c),
+            Component::CurDir => (),
+            _ => return None,
+        }
+    }
+
+    (!normalized.as_os_str().is_empty()).then_some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    const FILES: [(&str, &[u8]); 3] = [
+        ("./src/main.rs", b"fn main() {}\n"),
+        ("src/lib.rs", b"pub mod app;\n"),
+        ("logo.png", b"\x89PNG\xff"),
+    ];
+
+    fn zip(path: &Path) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+
+        zip.add_directory("src/", options).unwrap();
+        for (name, content) in FILES {
+            zip.start_file(name.trim_start_matches("./"), options)
+                .unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn tar_gz(path: &Path) {
+        let mut tar = tar::Builder::new(GzEncoder::new(
+            File::create(path).unwrap(),
+            Compression::default(),
+        ));
+
+        for (name, content) in FILES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, content).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn kind() {
+        assert_eq!(Kind::new(Path::new("sdk.zip")), Some(Kind::Zip));
+        assert_eq!(Kind::new(Path::new("sdk.TAR")), Some(Kind::Tar));
+        assert_eq!(Kind::new(Path::new("sdk-1.0.tar.gz")), Some(Kind::TarGz));
+        assert_eq!(Kind::new(Path::new("sdk.tgz")), Some(Kind::TarGz));
+        assert_eq!(Kind::new(Path::new("sdk.gz")), None);
+    }
+
+    #[test]
+    fn normalize() {
+        assert_eq!(
+            super::normalize(Path::new("./src/main.rs")),
+            Some(PathBuf::from("src/main.rs"))
+        );
+        assert_eq!(super::normalize(Path::new("../etc/passwd")), None);
+        assert_eq!(super::normalize(Path::new("/etc/passwd")), None);
+        assert_eq!(super::normalize(Path::new(".")), None);
+    }
+
+    #[test]
+    fn archives() {
+        let dir = TempDir::new("archive");
+
+        for (name, write) in [("sdk.zip", zip as fn(&Path)), ("sdk.tar.gz", tar_gz)] {
+            let archive = dir.path().join(name);
+            write(&archive);
+            assert!(ArchiveReader::is_archive(&archive));
+
+            let entries = ArchiveReader::list_files(&archive).unwrap();
+            assert_eq!(
+                entries,
+                vec![
+                    Entry {
+                        path: PathBuf::from("src/main.rs"),
+                        size: 13,
+                    },
+                    Entry {
+                        path: PathBuf::from("src/lib.rs"),
+                        size: 13,
+                    },
+                    Entry {
+                        path: PathBuf::from("logo.png"),
+                        size: 5,
+                    },
+                ]
+            );
+            assert_eq!(
+                ArchiveReader::contents(&archive, &entries[1..2], 64).unwrap(),
+                vec![b"pub mod app;\n".to_vec()]
+            );
+            assert_eq!(
+                ArchiveReader::contents(&archive, &entries[..2], 2).unwrap(),
+                vec![b"fn".to_vec(), b"pu".to_vec()]
+            );
+
+            let load = |path: &str| ArchiveReader::new(archive.clone(), PathBuf::from(path)).load();
+            assert_eq!(load("src/main.rs").unwrap(), "fn main() {}\n");
+            assert!(load("logo.png").is_err());
+            assert!(load("src/missing.rs").is_err());
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::git::git;
+use crate::reader::Reader;
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+
+pub struct CommitReader {
+    dir: PathBuf,
+    rev: String,
+    path: PathBuf,
+}
+
+impl CommitReader {
+    pub fn new(dir: PathBuf, rev: String, path: PathBuf) -> Self {
+        CommitReader { dir, rev, path }
+    }
+
+    pub fn list_files(dir: &Path, rev: &str) -> Result<Vec<PathBuf>> {
+        let numstat = git(
+            dir,
+            &[CommitReader::diff_args(rev), vec!["--numstat"]].concat(),
+        )?;
+        Ok(CommitReader::parse_numstat(&numstat))
+    }
+
+    pub fn subject(&self) -> Result<String> {
+        let path = self.path.to_string_lossy();
+        git(
+            &self.dir,
+            &["log", "-1", "--format=%s", &self.rev, "--", &path],
+        )
+        .map(|s| s.trim().to_string())
+    }
+
+    fn diff_args(rev: &str) -> Vec<&str> {
+        if rev.contains("..") {
+            vec!["diff", "--no-color", "--relative", "--no-renames", rev]
+        } else {
+            // a merge is shown against its first parent instead of as a combined diff
+            vec![
+                "show",
+                "--format=",
+                "--no-color",
+                "--relative",
+                "--no-renames",
+                "-m",
+                "--first-parent",
+                rev,
+            ]
+        }
+    }
+
+    fn parse_numstat(numstat: &str) -> Vec<PathBuf> {
+        numstat
+            .lines()
+            .filter_map(
+                |l| match l.splitn(3, '\t').collect::<Vec<&str>>().as_slice() {
+                    [added, _, path] if added.parse::<usize>().map(|a| a > 0).unwrap_or(false) => {
+                        Some(PathBuf::from(path))
+                    }
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+
+    fn parse_added_lines(diff: &str) -> String {
+        let mut in_hunk = false;
+
+        diff.lines()
+            .filter_map(|l| {
+                if l.starts_with("@@") {
+                    in_hunk = true;
+                    None
+                } else if l.starts_with("diff ") {
+                    in_hunk = false;
+                    None
+                } else if in_hunk {
+                    l.strip_prefix('+')
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+}
+
+impl Reader for CommitReader {
+    fn load(&self) -> Result<String> {
+        let path = self.path.to_string_lossy();
+        let diff = git(
+            &self.dir,
+            &[CommitReader::diff_args(&self.rev), vec!["--", &path]].concat(),
+        )?;
+        let text = CommitReader::parse_added_lines(&diff);
+
+        if text.trim().is_empty() {
+            Err(anyhow!("No added lines."))
+        } else {
+            Ok(text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempRepo;
+
+    #[test]
+    fn parse_numstat() {
+        let numstat = "3\t1\tsrc/main.rs\n0\t4\tsrc/app.rs\n-\t-\tdemo.gif\n10\t0\tREADME.md\n";
+        assert_eq!(
+            CommitReader::parse_numstat(numstat),
+            vec![PathBuf::from("src/main.rs"), PathBuf::from("README.md")]
+        );
+    }
+
+    #[test]
+    fn parse_added_lines() {
+        let diff = "diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,4 @@
+ use std::io;
++use std::fs;
+-use std::env;
+ 
+@@ -10,2 +11,3 @@ fn main() {
++    let a = 1;
++++b;
+     println!();";
+        assert_eq!(
+            CommitReader::parse_added_lines(diff),
+            "use std::fs;\n    let a = 1;\n++b;"
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let repo = TempRepo::new("commit-merge");
+        repo.file("a.rs", "fn a() {}\n");
+        repo.git(&["add", "."]);
+        repo.git(&["commit", "-q", "-m", "init"]);
+        repo.git(&["checkout", "-q", "-b", "side"]);
+        repo.file("b.rs", "fn b() {}\n");
+        repo.git(&["add", "."]);
+        repo.git(&["commit", "-q", "-m", "side"]);
+        repo.git(&["checkout", "-q", "main"]);
+        repo.file("a.rs", "fn a() {}\nfn c() {}\n");
+        repo.git(&["commit", "-q", "-a", "-m", "main"]);
+        repo.git(&["merge", "-q", "--no-edit", "side"]);
+        let dir = repo.path();
+
+        assert_eq!(
+            CommitReader::list_files(dir, "HEAD").unwrap(),
+            vec![PathBuf::from("b.rs")]
+        );
+        assert_eq!(
+            CommitReader::new(dir.to_path_buf(), "HEAD".to_owned(), PathBuf::from("b.rs"))
+                .load()
+                .unwrap(),
+            "fn b() {}"
+        );
+    }
+}
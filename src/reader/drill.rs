@@ -0,0 +1,132 @@
+use crate::reader::Reader;
+use crate::types::keystroke::KeyStats;
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const WEAK_KEYS: usize = 10;
+const MIN_LINE_LENGTH: usize = 8;
+const MAX_LINE_LENGTH: usize = 120;
+const MAX_READ: u64 = 64 * 1024; // bytes read of each file
+
+pub struct DrillReader {
+    files: Vec<PathBuf>,
+    weak_keys: Vec<(String, f64)>,
+    lines: usize,
+}
+
+impl DrillReader {
+    pub fn new(files: Vec<PathBuf>, stats: &KeyStats, lines: usize) -> Self {
+        DrillReader {
+            files,
+            weak_keys: DrillReader::weak_keys(stats),
+            lines,
+        }
+    }
+
+    // Error-prone keys and bigrams weighted by their error rate.
+    fn weak_keys(stats: &KeyStats) -> Vec<(String, f64)> {
+        stats
+            .error_prone_keys(WEAK_KEYS)
+            .into_iter()
+            .chain(stats.error_prone_bigrams(WEAK_KEYS))
+            .filter(|k| !k.key.trim().is_empty())
+            .map(|k| (k.key.clone(), k.error_rate()))
+            .collect()
+    }
+
+    fn score(&self, line: &str) -> f64 {
+        let weight: f64 = self
+            .weak_keys
+            .iter()
+            .map(|(key, rate)| line.matches(key.as_str()).count() as f64 * rate)
+            .sum();
+        weight / line.chars().count().max(1) as f64
+    }
+
+    // The start of the file, cut at the last whole line.
+    fn read(path: &Path) -> Result<String> {
+        let mut bytes = Vec::new();
+        File::open(path)?.take(MAX_READ).read_to_end(&mut bytes)?;
+
+        if bytes.len() as u64 == MAX_READ {
+            let end = bytes.iter().rposition(|b| *b == b'\n').unwrap_or(0);
+            bytes.truncate(end);
+        }
+        Ok(String::from_utf8(bytes)?)
+    }
+
+    fn drill(&self, texts: &[String]) -> String {
+        let mut seen = HashSet::new();
+        let mut lines: Vec<(f64, &str)> = texts
+            .iter()
+            .flat_map(|t| t.lines())
+            .map(|l| l.trim())
+            .filter(|l| (MIN_LINE_LENGTH..=MAX_LINE_LENGTH).contains(&l.chars().count()))
+            .filter(|l| seen.insert(*l))
+            .map(|l| (self.score(l), l))
+            .filter(|(score, _)| *score > 0.0)
+            .collect();
+        lines.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+        lines
+            .into_iter()
+            .take(self.lines)
+            .map(|(_, l)| l)
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+}
+
+impl Reader for DrillReader {
+    fn load(&self) -> Result<String> {
+        if self.weak_keys.is_empty() {
+            return Err(anyhow!("Not enough history to build a drill."));
+        }
+
+        let texts: Vec<String> = self
+            .files
+            .iter()
+            .filter_map(|f| DrillReader::read(f).ok())
+            .collect();
+        let text = self.drill(&texts);
+
+        if text.is_empty() {
+            Err(anyhow!("No lines found for the drill."))
+        } else {
+            Ok(text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::keystroke::Keystroke;
+
+    #[test]
+    fn drill() {
+        let keystrokes = vec![
+            Keystroke::new("-", '-', 100, true),
+            Keystroke::new(">", '.', 100, false),
+            Keystroke::new(">", '>', 100, true),
+            Keystroke::new("a", 'a', 100, true),
+        ];
+        let reader = DrillReader::new(Vec::new(), &KeyStats::new(&keystrokes), 2);
+        let texts = vec![
+            "fn main() {\n    let a = 1;\n}".to_owned(),
+            "fn f() -> u8 {\n    x.map(|a| a->b)\n    let b = 2;\n    x.map(|a| a->b)\n}"
+                .to_owned(),
+        ];
+
+        assert_eq!(reader.drill(&texts), "fn f() -> u8 {\nx.map(|a| a->b)");
+    }
+
+    #[test]
+    fn no_history() {
+        let reader = DrillReader::new(Vec::new(), &KeyStats::default(), 2);
+        assert!(reader.load().is_err());
+    }
+}
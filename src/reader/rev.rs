@@ -0,0 +1,177 @@
+use crate::git::{git_bytes, git_with_input};
+use crate::reader::Reader;
+use anyhow::{anyhow, Result};
+use std::io::{self, BufRead, Read};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Blob {
+    pub path: PathBuf, // relative to the directory
+    pub id: String,
+    pub size: u64,
+}
+
+// Reads a file as it is at a revision, from the object store of the repository.
+pub struct RevReader {
+    dir: PathBuf,
+    rev: String,
+    path: PathBuf,
+}
+
+impl RevReader {
+    pub fn new(dir: PathBuf, rev: String, path: PathBuf) -> Self {
+        RevReader { dir, rev, path }
+    }
+
+    // Files under the directory in the tree of the revision.
+    pub fn list_files(dir: &Path, rev: &str) -> Result<Vec<Blob>> {
+        let tree = git_bytes(dir, &["ls-tree", "-r", "-l", "-z", rev])?;
+        Ok(RevReader::parse_tree(&String::from_utf8_lossy(&tree)))
+    }
+
+    // The first `limit` bytes of the blobs, read with a single git process.
+    pub fn contents(dir: &Path, blobs: &[Blob], limit: u64) -> Result<Vec<Vec<u8>>> {
+        if blobs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let input = blobs
+            .iter()
+            .map(|b| format!("{}\n", b.id))
+            .collect::<String>();
+        git_with_input(
+            dir,
+            &["cat-file", "--batch"],
+            input.into_bytes(),
+            |output| RevReader::parse_batch(output, blobs.len(), limit),
+        )
+    }
+
+    // `<mode> <type> <object> <size>\t<path>`, separated by NUL.
+    fn parse_tree(tree: &str) -> Vec<Blob> {
+        tree.split('\0')
+            .filter_map(|entry| {
+                let (info, path) = entry.split_once('\t')?;
+                match info.split_whitespace().collect::<Vec<&str>>().as_slice() {
+                    [mode, "blob", id, size] if *mode != "120000" => Some(Blob {
+                        path: PathBuf::from(path),
+                        id: id.to_string(),
+                        size: size.parse().ok()?,
+                    }),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    // `<object> <type> <size>\n<contents>\n` for each object, keeping the first `limit` bytes
+    // of the contents and skipping the rest.
+    fn parse_batch(mut output: impl BufRead, count: usize, limit: u64) -> Result<Vec<Vec<u8>>> {
+        let unexpected = || anyhow!("Unexpected output of git cat-file.");
+        let mut contents = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let mut header = Vec::new();
+            output.read_until(b'\n', &mut header)?;
+            if header.pop() != Some(b'\n') {
+                return Err(unexpected());
+            }
+            let header = String::from_utf8_lossy(&header).to_string();
+            let size: u64 = match header.split_whitespace().collect::<Vec<&str>>().as_slice() {
+                [_, _, size] => size.parse().map_err(|_| unexpected())?,
+                _ => return Err(anyhow!("Object not found: {}", header)),
+            };
+            let kept = size.min(limit);
+
+            let mut content = Vec::new();
+            output.by_ref().take(kept).read_to_end(&mut content)?;
+            let skipped = io::copy(&mut output.by_ref().take(size - kept + 1), &mut io::sink())?;
+            if content.len() as u64 != kept || skipped != size - kept + 1 {
+                return Err(unexpected());
+            }
+            contents.push(content);
+        }
+
+        Ok(contents)
+    }
+}
+
+impl Reader for RevReader {
+    fn load(&self) -> Result<String> {
+        let object = format!("{}:./{}", self.rev, self.path.to_string_lossy());
+        let blob = git_bytes(&self.dir, &["cat-file", "blob", &object])?;
+        String::from_utf8(blob).map_err(|_| anyhow!("{} is not a text file.", object))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempRepo;
+
+    #[test]
+    fn parse_tree() {
+        let tree = "100644 blob 1111 120\tsrc/main.rs\x00100755 blob 2222 7\tbuild.sh\x00120000 blob 3333 4\tlink\x00160000 commit 4444 -\tsub\x00";
+        assert_eq!(
+            RevReader::parse_tree(tree),
+            vec![
+                Blob {
+                    path: PathBuf::from("src/main.rs"),
+                    id: "1111".to_owned(),
+                    size: 120,
+                },
+                Blob {
+                    path: PathBuf::from("build.sh"),
+                    id: "2222".to_owned(),
+                    size: 7,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_batch() {
+        let output: &[u8] = b"1111 blob 3\nfn\n\n2222 blob 0\n\n";
+        assert_eq!(
+            RevReader::parse_batch(output, 2, 64).unwrap(),
+            vec![b"fn\n".to_vec(), Vec::new()]
+        );
+        assert_eq!(
+            RevReader::parse_batch(output, 2, 1).unwrap(),
+            vec![b"f".to_vec(), Vec::new()]
+        );
+        assert!(RevReader::parse_batch(&b"1111 missing\n"[..], 1, 64).is_err());
+        assert!(RevReader::parse_batch(&b"1111 blob 10\nfn\n"[..], 1, 64).is_err());
+        assert!(RevReader::parse_batch(&b"1111 blob 10\nfn\n"[..], 1, 1).is_err());
+    }
+
+    #[test]
+    fn load() {
+        let repo = TempRepo::new("rev-load");
+        repo.file("Cargo.toml", "[package]\n");
+        repo.file("src/main.rs", "fn main() {}\n");
+        repo.git(&["add", "."]);
+        repo.git(&["commit", "-q", "-m", "init"]);
+        repo.file("Cargo.toml", "[workspace]\n");
+        let dir = repo.path();
+
+        let blobs: Vec<Blob> = RevReader::list_files(dir, "HEAD")
+            .unwrap()
+            .into_iter()
+            .filter(|b| b.path == Path::new("Cargo.toml"))
+            .collect();
+        assert_eq!(blobs.len(), 1);
+
+        let reader = RevReader::new(
+            dir.to_path_buf(),
+            "HEAD".to_owned(),
+            PathBuf::from("Cargo.toml"),
+        );
+        let text = reader.load().unwrap();
+        assert_eq!(text, "[package]\n");
+        assert_eq!(
+            RevReader::contents(dir, &blobs, 64).unwrap(),
+            vec![text.into_bytes()]
+        );
+    }
+}
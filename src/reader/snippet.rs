@@ -0,0 +1,496 @@
+use rand::Rng;
+use std::path::Path;
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+// Scopes that name a function, method or class at its definition.
+const DEFINITIONS: [&str; 5] = [
+    "entity.name.function",
+    "entity.name.class",
+    "entity.name.struct",
+    "entity.name.impl",
+    "entity.name.trait",
+];
+const MAX_SIGNATURE_LINES: usize = 10;
+
+// What the parser knows about a line of code.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Code {
+    definition: bool,
+    depth: i32, // braces opened minus braces closed, outside strings and comments
+    opens: bool,
+    last: Option<char>, // last character outside strings and comments
+}
+
+// A part of a file to type, keeping the line number it starts at in the file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snippet {
+    pub text: String,
+    pub first_line: usize,
+}
+
+impl Snippet {
+    pub fn whole(text: &str) -> Self {
+        Snippet {
+            text: text.to_owned(),
+            first_line: 1,
+        }
+    }
+
+    // A random block of `lines` non-trivial lines after the license header, starting at
+    // the top of a block where possible and running on to close the last block.
+    pub fn pick<R: Rng>(text: &str, lines: usize, rng: &mut R) -> Self {
+        let all: Vec<&str> = text.lines().collect();
+        let body = Snippet::header_len(&all);
+        let windows: Vec<(usize, usize)> = (body..all.len())
+            .filter(|i| !Snippet::is_trivial(all[*i]))
+            .filter_map(|start| Snippet::window(&all, start, lines).map(|end| (start, end)))
+            .collect();
+        let boundaries: Vec<(usize, usize)> = windows
+            .iter()
+            .filter(|(start, _)| Snippet::is_boundary(&all, body, *start))
+            .copied()
+            .collect();
+        let candidates = if boundaries.is_empty() {
+            windows
+        } else {
+            boundaries
+        };
+
+        match candidates.as_slice() {
+            [] => {
+                // shorter than the snippet: everything after the header
+                let start = (body..all.len())
+                    .find(|i| !all[*i].trim().is_empty())
+                    .unwrap_or(0);
+                Snippet {
+                    text: all[start..].join("\n"),
+                    first_line: start + 1,
+                }
+            }
+            candidates => {
+                let (start, end) = candidates[rng.gen_range(0..candidates.len())];
+                Snippet {
+                    text: all[start..end].join("\n"),
+                    first_line: start + 1,
+                }
+            }
+        }
+    }
+
+    // A random whole function, method or class with at most `lines` non-trivial lines, found
+    // from the scopes of the syntax of the file. Falls back to a window of `lines` lines.
+    pub fn function<R: Rng>(text: &str, path: &Path, lines: usize, rng: &mut R) -> Self {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let units = match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(|e| syntax_set.find_syntax_by_extension(e))
+        {
+            Some(syntax) => Snippet::units(text, syntax, &syntax_set),
+            None => Vec::new(),
+        };
+        let all: Vec<&str> = text.lines().collect();
+        let size = |(start, end): &(usize, usize)| {
+            all[*start..*end]
+                .iter()
+                .filter(|l| !Snippet::is_trivial(l))
+                .count()
+        };
+        let fitting: Vec<(usize, usize)> = units
+            .iter()
+            .filter(|u| size(u) <= lines.max(1))
+            .copied()
+            .collect();
+
+        let unit = if fitting.is_empty() {
+            units.iter().min_by_key(|u| size(u)).copied()
+        } else {
+            Some(fitting[rng.gen_range(0..fitting.len())])
+        };
+
+        match unit {
+            Some((start, end)) => Snippet {
+                text: all[start..end].join("\n"),
+                first_line: start + 1,
+            },
+            None => Snippet::pick(text, lines, rng),
+        }
+    }
+
+    // Line ranges of the definitions in the text, with the attributes or decorators above them.
+    fn units(text: &str, syntax: &SyntaxReference, syntax_set: &SyntaxSet) -> Vec<(usize, usize)> {
+        let code = Snippet::parse(text, syntax, syntax_set);
+        let all: Vec<&str> = text.lines().collect();
+        let indent = |l: &str| l.len() - l.trim_start().len();
+
+        code.iter()
+            .enumerate()
+            .filter(|(_, c)| c.definition)
+            .filter_map(|(start, _)| {
+                let mut depth = 0;
+                let mut opened = false;
+
+                for (i, c) in code
+                    .iter()
+                    .enumerate()
+                    .skip(start)
+                    .take(MAX_SIGNATURE_LINES)
+                {
+                    depth += c.depth;
+                    opened = opened || c.opens;
+
+                    if opened {
+                        // brace blocks end where the braces are balanced again
+                        let end = (i..code.len()).find(|j| {
+                            if *j > i {
+                                depth += code[*j].depth;
+                            }
+                            depth <= 0
+                        })?;
+                        return Some((start, end + 1));
+                    }
+                    match c.last {
+                        Some(';') => return None,
+                        Some(':') => {
+                            // indented blocks end before the next line that is not indented more
+                            let end = (i + 1..all.len())
+                                .take_while(|j| {
+                                    all[*j].trim().is_empty()
+                                        || indent(all[*j]) > indent(all[start])
+                                })
+                                .filter(|j| !all[*j].trim().is_empty())
+                                .last()?;
+                            return Some((start, end + 1));
+                        }
+                        _ => (),
+                    }
+                }
+
+                None
+            })
+            .filter(|(start, end)| end - start > 1)
+            .map(|(start, end)| {
+                let above = all[..start]
+                    .iter()
+                    .rev()
+                    .take_while(|l| {
+                        let l = l.trim_start();
+                        l.starts_with("#[") || l.starts_with('@')
+                    })
+                    .count();
+                (start - above, end)
+            })
+            .collect()
+    }
+
+    fn parse(text: &str, syntax: &SyntaxReference, syntax_set: &SyntaxSet) -> Vec<Code> {
+        let definitions: Vec<Scope> = DEFINITIONS
+            .iter()
+            .filter_map(|d| Scope::new(d).ok())
+            .collect();
+        let ignored: Vec<Scope> = ["string", "comment"]
+            .iter()
+            .filter_map(|d| Scope::new(d).ok())
+            .collect();
+        let mut state = ParseState::new(syntax);
+        let mut stack = ScopeStack::new();
+
+        LinesWithEndings::from(text)
+            .map(|line| {
+                let mut ops = state.parse_line(line, syntax_set).into_iter().peekable();
+                let mut code = Code::default();
+
+                for (i, c) in line.char_indices() {
+                    while let Some((_, op)) = ops.next_if(|(at, _)| *at <= i) {
+                        stack.apply(&op);
+                    }
+
+                    let scopes = stack.as_slice();
+                    code.definition = code.definition
+                        || scopes
+                            .iter()
+                            .any(|s| definitions.iter().any(|d| d.is_prefix_of(*s)));
+                    if scopes
+                        .iter()
+                        .any(|s| ignored.iter().any(|d| d.is_prefix_of(*s)))
+                        || c.is_whitespace()
+                    {
+                        continue;
+                    }
+
+                    match c {
+                        '{' => {
+                            code.depth += 1;
+                            code.opens = true;
+                        }
+                        '}' => code.depth -= 1,
+                        _ => (),
+                    }
+                    code.last = Some(c);
+                }
+                for (_, op) in ops {
+                    stack.apply(&op);
+                }
+
+                code
+            })
+            .collect()
+    }
+
+    // The end of a window from `start` with `lines` non-trivial lines, if the file is long
+    // enough. Brackets left open are closed within another `lines` lines.
+    fn window(all: &[&str], start: usize, lines: usize) -> Option<usize> {
+        let lines = lines.max(1);
+        let mut count = 0;
+        let mut depth = 0;
+
+        for (i, line) in all.iter().enumerate().skip(start) {
+            depth += Snippet::depth(line);
+            if !Snippet::is_trivial(line) {
+                count += 1;
+            }
+            if count == lines {
+                let rest = &all[i + 1..];
+                let closing = if depth > 0 {
+                    rest.iter()
+                        .take(lines)
+                        .scan(depth, |depth, l| {
+                            let open = *depth > 0;
+                            *depth += Snippet::depth(l);
+                            Some(open)
+                        })
+                        .take_while(|open| *open)
+                        .count()
+                } else {
+                    rest.iter().take_while(|l| Snippet::is_closing(l)).count()
+                };
+                return Some(i + 1 + closing);
+            }
+        }
+
+        None
+    }
+
+    fn depth(line: &str) -> i32 {
+        line.chars()
+            .map(|c| match c {
+                '{' | '(' | '[' => 1,
+                '}' | ')' | ']' => -1,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    // Lines of comments and blank lines at the top of the file.
+    fn header_len(all: &[&str]) -> usize {
+        let mut block: Option<&str> = None;
+
+        for (i, line) in all.iter().enumerate() {
+            let line = line.trim();
+
+            if let Some(end) = block {
+                if line.contains(end) {
+                    block = None;
+                }
+                continue;
+            }
+
+            match [
+                ("/*", "*/"),
+                ("<!--", "-->"),
+                ("\"\"\"", "\"\"\""),
+                ("{-", "-}"),
+            ]
+            .iter()
+            .find(|(start, _)| line.starts_with(start))
+            {
+                Some((start, end)) => {
+                    if !line[start.len()..].contains(end) {
+                        block = Some(end);
+                    }
+                }
+                None if line.is_empty() || Snippet::is_line_comment(line) => (),
+                None => return i,
+            }
+        }
+
+        all.len()
+    }
+
+    fn is_line_comment(line: &str) -> bool {
+        ["//", "--", ";;", "%"].iter().any(|c| line.starts_with(c))
+            || line == "#"
+            || line.starts_with("# ")
+            || line.starts_with("#!")
+    }
+
+    // Blank lines, lone brackets and the like are typed on the way but not counted.
+    fn is_trivial(line: &str) -> bool {
+        line.chars().filter(|c| c.is_alphanumeric()).count() < 2 && !line.trim().ends_with('{')
+    }
+
+    fn is_closing(line: &str) -> bool {
+        let line = line.trim();
+        !line.is_empty()
+            && (line.chars().all(|c| ")]};,".contains(c)) || ["end", "fi", "done"].contains(&line))
+    }
+
+    // The first line of the body, or a line at the outermost indentation after a blank line
+    // or the end of a block.
+    fn is_boundary(all: &[&str], body: usize, start: usize) -> bool {
+        let indent = |l: &str| l.len() - l.trim_start().len();
+        let outermost = all[body..]
+            .iter()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| indent(l))
+            .min()
+            .unwrap_or(0);
+        let previous = all[body..start].iter().rev().find(|l| !l.trim().is_empty());
+
+        indent(all[start]) == outermost
+            && !Snippet::is_import(all[start])
+            && match previous {
+                None => true,
+                Some(previous) => {
+                    all[start - 1].trim().is_empty()
+                        || Snippet::is_closing(previous)
+                        || previous.trim_end().ends_with(';')
+                }
+            }
+    }
+
+    fn is_import(line: &str) -> bool {
+        [
+            "use ", "import ", "from ", "#include", "require", "package ",
+        ]
+        .iter()
+        .any(|i| line.trim_start().starts_with(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    const TEXT: &str = "// Copyright (c) 2022
+// MIT License
+/*
+ * header
+ */
+
+use std::io;
+
+fn a() {
+    let x = 1;
+    x
+}
+
+fn b() {
+    let y = 2;
+    y
+}";
+
+    #[test]
+    fn pick() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for _ in 0..10 {
+            let snippet = Snippet::pick(TEXT, 2, &mut rng);
+            assert!(
+                snippet
+                    == Snippet {
+                        text: "fn a() {\n    let x = 1;\n    x\n}".to_owned(),
+                        first_line: 9,
+                    }
+                    || snippet
+                        == Snippet {
+                            text: "fn b() {\n    let y = 2;\n    y\n}".to_owned(),
+                            first_line: 14,
+                        },
+                "{:?}",
+                snippet
+            );
+        }
+    }
+
+    #[test]
+    fn short() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(
+            Snippet::pick(TEXT, 100, &mut rng),
+            Snippet {
+                text: TEXT.lines().skip(6).collect::<Vec<&str>>().join("\n"),
+                first_line: 7,
+            }
+        );
+        assert_eq!(Snippet::pick("", 5, &mut rng).text, "");
+    }
+
+    #[test]
+    fn function() {
+        let text = "use std::io;
+
+#[test]
+fn a() {
+    let s = \"}\";
+    s
+}
+
+impl A {
+    fn b(&self) -> u8 {
+        1
+    }
+}
+
+trait T {
+    fn c();
+}";
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let syntax = syntax_set.find_syntax_by_extension("rs").unwrap();
+        assert_eq!(
+            Snippet::units(text, syntax, &syntax_set),
+            vec![(2, 7), (8, 13), (9, 12), (14, 17)]
+        );
+
+        let path = Path::new("main.rs");
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(
+            Snippet::function(text, path, 1, &mut rng),
+            Snippet {
+                text: "    fn b(&self) -> u8 {\n        1\n    }".to_owned(),
+                first_line: 10,
+            }
+        );
+        for _ in 0..10 {
+            let snippet = Snippet::function(text, path, 2, &mut rng);
+            assert!([9, 10, 15].contains(&snippet.first_line), "{:?}", snippet);
+        }
+    }
+
+    #[test]
+    fn function_indented() {
+        let text = "@dec\ndef f(a):\n    if a:\n        return a\n\n    return 1\n\nx = f(1)";
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(
+            Snippet::function(text, Path::new("a.py"), 10, &mut rng),
+            Snippet {
+                text: "@dec\ndef f(a):\n    if a:\n        return a\n\n    return 1".to_owned(),
+                first_line: 1,
+            }
+        );
+        assert_eq!(
+            Snippet::function(text, Path::new("a.txt"), 1, &mut rng).first_line,
+            1
+        );
+    }
+
+    #[test]
+    fn header() {
+        let lines: Vec<&str> = TEXT.lines().collect();
+        assert_eq!(Snippet::header_len(&lines), 6);
+        assert_eq!(Snippet::header_len(&["#!/bin/sh", "# x", "echo"]), 2);
+        assert_eq!(Snippet::header_len(&["#include <a>"]), 0);
+    }
+}
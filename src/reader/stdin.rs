@@ -0,0 +1,46 @@
+use crate::reader::Reader;
+use anyhow::{anyhow, Result};
+use crossterm::tty::IsTty;
+use std::io::{self, Read};
+
+// Reads the text piped into gitype. Keys are still read from the terminal.
+pub struct StdinReader;
+
+impl StdinReader {
+    fn read(mut input: impl Read) -> Result<String> {
+        let mut text = String::new();
+        input.read_to_string(&mut text)?;
+
+        if text.trim().is_empty() {
+            Err(anyhow!("No input."))
+        } else {
+            Ok(text)
+        }
+    }
+}
+
+impl Reader for StdinReader {
+    fn load(&self) -> Result<String> {
+        let stdin = io::stdin();
+
+        if stdin.is_tty() {
+            return Err(anyhow!("Nothing is piped into stdin."));
+        }
+        StdinReader::read(stdin.lock())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        assert_eq!(
+            StdinReader::read("fn main() {}\n".as_bytes()).unwrap(),
+            "fn main() {}\n"
+        );
+        assert!(StdinReader::read(" \n".as_bytes()).is_err());
+        assert!(StdinReader::read(&[0xff, 0xfe][..]).is_err());
+    }
+}
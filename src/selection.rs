@@ -0,0 +1,194 @@
+use anyhow::{anyhow, Result};
+use ignore::overrides::{Override, OverrideBuilder};
+use std::path::Path;
+
+const LANGUAGES: [(&str, &[&str]); 24] = [
+    ("c", &["c", "h"]),
+    ("cpp", &["cc", "cpp", "cxx", "hh", "hpp", "hxx"]),
+    ("csharp", &["cs"]),
+    ("css", &["css", "scss", "sass", "less"]),
+    ("elixir", &["ex", "exs"]),
+    ("go", &["go"]),
+    ("haskell", &["hs"]),
+    ("html", &["html", "htm"]),
+    ("java", &["java"]),
+    ("javascript", &["js", "jsx", "mjs", "cjs"]),
+    ("json", &["json"]),
+    ("kotlin", &["kt", "kts"]),
+    ("lua", &["lua"]),
+    ("markdown", &["md", "markdown"]),
+    ("php", &["php"]),
+    ("protobuf", &["proto"]),
+    ("python", &["py", "pyi"]),
+    ("ruby", &["rb"]),
+    ("rust", &["rs"]),
+    ("scala", &["scala"]),
+    ("shell", &["sh", "bash", "zsh"]),
+    ("swift", &["swift"]),
+    ("toml", &["toml"]),
+    ("typescript", &["ts", "tsx", "mts", "cts"]),
+];
+
+// Which files to type from: extensions, languages, and include and exclude globs
+// relative to the root.
+#[derive(Clone, Debug)]
+pub struct Selection {
+    extensions: Vec<String>,
+    globs: Override,
+}
+
+impl Selection {
+    // `extensions` and `languages` may be comma separated.
+    pub fn new(
+        root: &Path,
+        extensions: &[String],
+        languages: &[String],
+        include: &[String],
+        exclude: &[String],
+    ) -> Result<Self> {
+        let mut selected: Vec<String> = split(extensions)
+            .map(|e| e.trim_start_matches('.').to_lowercase())
+            .collect();
+
+        for language in split(languages) {
+            match LANGUAGES
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(language))
+            {
+                Some((_, extensions)) => selected.extend(extensions.iter().map(|e| e.to_string())),
+                None => {
+                    return Err(anyhow!(
+                        "Unknown language: {}. Available languages: {}",
+                        language,
+                        LANGUAGES
+                            .iter()
+                            .map(|(name, _)| *name)
+                            .collect::<Vec<&str>>()
+                            .join(", ")
+                    ))
+                }
+            }
+        }
+        let mut extensions: Vec<String> = Vec::new();
+        for extension in selected {
+            if !extensions.contains(&extension) {
+                extensions.push(extension);
+            }
+        }
+
+        let mut globs = OverrideBuilder::new(root);
+        for glob in include {
+            globs
+                .add(glob)
+                .map_err(|e| anyhow!("Invalid glob {}: {}", glob, e))?;
+        }
+        for glob in exclude {
+            globs
+                .add(&format!("!{}", glob))
+                .map_err(|e| anyhow!("Invalid glob {}: {}", glob, e))?;
+        }
+
+        Ok(Selection {
+            extensions,
+            globs: globs.build()?,
+        })
+    }
+
+    pub fn extensions(&self) -> &[String] {
+        &self.extensions
+    }
+
+    // Files need an extension unless an include glob names them.
+    pub fn matches(&self, path: &Path) -> bool {
+        let extension = path
+            .extension()
+            .and_then(|f| f.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        match self.globs.matched(path, false) {
+            ignore::Match::Ignore(_) => false,
+            ignore::Match::Whitelist(_) if extension.is_empty() => true,
+            _ => {
+                !extension.is_empty()
+                    && (self.extensions.is_empty() || self.extensions.contains(&extension))
+            }
+        }
+    }
+}
+
+fn split(values: &[String]) -> impl Iterator<Item = &str> {
+    values
+        .iter()
+        .flat_map(|v| v.split(','))
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn extensions() {
+        let selection =
+            Selection::new(Path::new("."), &strings(&["rs,.TOML"]), &[], &[], &[]).unwrap();
+        assert!(selection.matches(Path::new("src/main.rs")));
+        assert!(selection.matches(Path::new("Cargo.toml")));
+        assert!(!selection.matches(Path::new("index.ts")));
+        assert!(!selection.matches(Path::new("Makefile")));
+
+        let all = Selection::new(Path::new("."), &[], &[], &[], &[]).unwrap();
+        assert!(all.matches(Path::new("index.ts")));
+        assert!(!all.matches(Path::new("Makefile")));
+    }
+
+    #[test]
+    fn languages() {
+        let selection = Selection::new(
+            Path::new("."),
+            &strings(&["proto"]),
+            &strings(&["Rust", "typescript"]),
+            &[],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            selection.extensions(),
+            strings(&["proto", "rs", "ts", "tsx", "mts", "cts"])
+        );
+        assert!(selection.matches(Path::new("web/app.tsx")));
+        assert!(!selection.matches(Path::new("web/app.js")));
+
+        assert!(Selection::new(Path::new("."), &[], &strings(&["cobol"]), &[], &[]).is_err());
+    }
+
+    #[test]
+    fn globs() {
+        let selection = Selection::new(
+            Path::new("."),
+            &strings(&["rs"]),
+            &[],
+            &strings(&["crates/**", "Makefile"]),
+            &strings(&["**/generated/**", "*_test.rs"]),
+        )
+        .unwrap();
+        assert!(selection.matches(Path::new("crates/core/src/lib.rs")));
+        assert!(selection.matches(Path::new("Makefile")));
+        assert!(!selection.matches(Path::new("src/main.rs")));
+        assert!(!selection.matches(Path::new("crates/core/src/generated/api.rs")));
+        assert!(!selection.matches(Path::new("crates/core/src/lib_test.rs")));
+        assert!(!selection.matches(Path::new("crates/core/README.md")));
+
+        let excluded =
+            Selection::new(Path::new("."), &[], &[], &[], &strings(&["proto/**"])).unwrap();
+        assert!(excluded.matches(Path::new("src/main.rs")));
+        assert!(!excluded.matches(Path::new("proto/api.rs")));
+
+        assert!(Selection::new(Path::new("."), &[], &[], &strings(&["[a"]), &[]).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+// Fixtures shared by the tests.
+use crate::git::git;
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+// A directory of a test, removed when the test ends, even when it fails.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    // `name` has to be unique among the tests, which run in parallel.
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("gitype-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    // Writes a file relative to the directory, with its parent directories.
+    pub fn file(&self, name: &str, content: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.0.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, content).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// A git repository on the branch `main` in a TempDir, committing with a fixed identity since
+// the machine may have none.
+pub struct TempRepo(TempDir);
+
+impl TempRepo {
+    pub fn new(name: &str) -> Self {
+        let repo = TempRepo(TempDir::new(name));
+        repo.git(&["init", "-q", "-b", "main"]);
+        repo
+    }
+
+    pub fn git(&self, args: &[&str]) -> String {
+        let args = [
+            &[
+                "-c",
+                "user.name=gitype",
+                "-c",
+                "user.email=gitype@localhost",
+            ],
+            args,
+        ]
+        .concat();
+        git(self.path(), &args).unwrap()
+    }
+}
+
+impl Deref for TempRepo {
+    type Target = TempDir;
+
+    fn deref(&self) -> &TempDir {
+        &self.0
+    }
+}
@@ -1,2 +1,4 @@
+pub mod clock;
+pub mod keystroke;
 pub mod line;
 pub mod typing;
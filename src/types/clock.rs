@@ -0,0 +1,55 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+// Time elapsed since the clock was created. A manual clock only moves when advanced,
+// which keeps scripted sessions deterministic.
+#[derive(Clone, Debug)]
+pub enum Clock {
+    System(Instant),
+    Manual(Rc<Cell<Duration>>),
+}
+
+impl Clock {
+    pub fn system() -> Self {
+        Clock::System(Instant::now())
+    }
+
+    pub fn manual() -> Self {
+        Clock::Manual(Rc::new(Cell::new(Duration::ZERO)))
+    }
+
+    pub fn now(&self) -> Duration {
+        match self {
+            Clock::System(start) => start.elapsed(),
+            Clock::Manual(now) => now.get(),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        if let Clock::Manual(now) = self {
+            now.set(now.get() + duration);
+        }
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::system()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual() {
+        let clock = Clock::manual();
+        let shared = clock.clone();
+        assert_eq!(clock.now(), Duration::ZERO);
+
+        shared.advance(Duration::from_millis(1500));
+        assert_eq!(clock.now(), Duration::from_millis(1500));
+    }
+}
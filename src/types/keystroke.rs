@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keystroke {
+    pub expected: String,
+    pub actual: char,
+    pub latency: u64, // milliseconds since the previous keystroke
+    pub correct: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyStat {
+    pub key: String,
+    pub count: usize,
+    pub errors: usize,
+    pub latency: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct KeyStats {
+    keys: Vec<KeyStat>,
+    bigrams: Vec<KeyStat>,
+}
+
+impl Keystroke {
+    pub fn new(expected: &str, actual: char, latency: u64, correct: bool) -> Self {
+        Keystroke {
+            expected: expected.to_string(),
+            actual,
+            latency,
+            correct,
+        }
+    }
+}
+
+impl KeyStat {
+    pub fn avg_latency(&self) -> u64 {
+        self.latency / self.count.max(1) as u64
+    }
+
+    pub fn error_rate(&self) -> f64 {
+        self.errors as f64 / self.count.max(1) as f64
+    }
+
+    fn merge(&mut self, other: &KeyStat) {
+        self.count += other.count;
+        self.errors += other.errors;
+        self.latency += other.latency;
+    }
+
+    fn add(&mut self, keystroke: &Keystroke) {
+        self.count += 1;
+        self.latency += keystroke.latency;
+        if !keystroke.correct {
+            self.errors += 1;
+        }
+    }
+}
+
+impl KeyStats {
+    pub fn new(keystrokes: &[Keystroke]) -> Self {
+        let mut keys: HashMap<String, KeyStat> = HashMap::new();
+        let mut bigrams: HashMap<String, KeyStat> = HashMap::new();
+
+        for (i, keystroke) in keystrokes.iter().enumerate() {
+            keys.entry(keystroke.expected.clone())
+                .or_insert_with_key(|key| KeyStat {
+                    key: key.clone(),
+                    ..KeyStat::default()
+                })
+                .add(keystroke);
+
+            if let Some(prev) = i.checked_sub(1).and_then(|i| keystrokes.get(i)) {
+                if prev.expected != keystroke.expected || prev.correct {
+                    bigrams
+                        .entry(prev.expected.clone() + &keystroke.expected)
+                        .or_insert_with_key(|key| KeyStat {
+                            key: key.clone(),
+                            ..KeyStat::default()
+                        })
+                        .add(keystroke);
+                }
+            }
+        }
+
+        KeyStats {
+            keys: keys.into_values().collect(),
+            bigrams: bigrams.into_values().collect(),
+        }
+    }
+
+    // Stats of several sessions, so that no bigram spans two of them.
+    pub fn merge(stats: impl IntoIterator<Item = KeyStats>) -> Self {
+        let mut keys: HashMap<String, KeyStat> = HashMap::new();
+        let mut bigrams: HashMap<String, KeyStat> = HashMap::new();
+
+        for s in stats {
+            for (merged, stats) in [(&mut keys, s.keys), (&mut bigrams, s.bigrams)] {
+                for stat in stats {
+                    merged
+                        .entry(stat.key.clone())
+                        .or_insert_with_key(|key| KeyStat {
+                            key: key.clone(),
+                            ..KeyStat::default()
+                        })
+                        .merge(&stat);
+                }
+            }
+        }
+
+        KeyStats {
+            keys: keys.into_values().collect(),
+            bigrams: bigrams.into_values().collect(),
+        }
+    }
+
+    pub fn keys(&self) -> &[KeyStat] {
+        &self.keys
+    }
+
+    pub fn slowest_keys(&self, n: usize) -> Vec<KeyStat> {
+        KeyStats::slowest(&self.keys, n)
+    }
+
+    pub fn slowest_bigrams(&self, n: usize) -> Vec<KeyStat> {
+        KeyStats::slowest(&self.bigrams, n)
+    }
+
+    pub fn error_prone_keys(&self, n: usize) -> Vec<KeyStat> {
+        KeyStats::error_prone(&self.keys, n)
+    }
+
+    pub fn error_prone_bigrams(&self, n: usize) -> Vec<KeyStat> {
+        KeyStats::error_prone(&self.bigrams, n)
+    }
+
+    fn slowest(stats: &[KeyStat], n: usize) -> Vec<KeyStat> {
+        let mut stats = stats.to_vec();
+        stats.sort_by(|a, b| {
+            b.avg_latency()
+                .cmp(&a.avg_latency())
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        stats.into_iter().take(n).collect()
+    }
+
+    fn error_prone(stats: &[KeyStat], n: usize) -> Vec<KeyStat> {
+        let mut stats: Vec<KeyStat> = stats.iter().filter(|s| s.errors > 0).cloned().collect();
+        stats.sort_by(|a, b| {
+            b.error_rate()
+                .partial_cmp(&a.error_rate())
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| b.errors.cmp(&a.errors))
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        stats.into_iter().take(n).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keystrokes() -> Vec<Keystroke> {
+        vec![
+            Keystroke::new("-", '-', 100, true),
+            Keystroke::new(">", '.', 400, false),
+            Keystroke::new(">", '>', 200, true),
+            Keystroke::new(":", ':', 100, true),
+            Keystroke::new(":", ':', 300, true),
+            Keystroke::new("-", '-', 100, true),
+            Keystroke::new(">", '>', 100, true),
+        ]
+    }
+
+    #[test]
+    fn keys() {
+        let stats = KeyStats::new(&keystrokes());
+
+        let slowest = stats.slowest_keys(2);
+        assert_eq!(slowest[0].key, ">");
+        assert_eq!(slowest[0].count, 3);
+        assert_eq!(slowest[0].errors, 1);
+        assert_eq!(slowest[0].avg_latency(), 233);
+        assert_eq!(slowest[1].key, ":");
+
+        let error_prone = stats.error_prone_keys(5);
+        assert_eq!(error_prone.len(), 1);
+        assert_eq!(error_prone[0].key, ">");
+    }
+
+    #[test]
+    fn bigrams() {
+        let stats = KeyStats::new(&keystrokes());
+
+        let slowest = stats.slowest_bigrams(1);
+        assert_eq!(slowest[0].key, "::");
+        assert_eq!(slowest[0].avg_latency(), 300);
+
+        let error_prone = stats.error_prone_bigrams(5);
+        assert_eq!(error_prone.len(), 1);
+        assert_eq!(error_prone[0].key, "->");
+        assert_eq!(error_prone[0].count, 2);
+        assert_eq!(error_prone[0].errors, 1);
+    }
+
+    #[test]
+    fn merge() {
+        let keystrokes = keystrokes();
+        let (first, second) = keystrokes.split_at(3);
+        let stats = KeyStats::merge([KeyStats::new(first), KeyStats::new(second)]);
+
+        let key = stats.slowest_keys(1);
+        assert_eq!(key[0].key, ">");
+        assert_eq!(key[0].count, 3);
+        assert_eq!(key[0].errors, 1);
+
+        // `>` `:` is split between the sessions
+        assert!(stats.slowest_bigrams(10).iter().all(|s| s.key != ">:"));
+        assert_eq!(stats.error_prone_bigrams(5)[0].count, 2);
+    }
+}
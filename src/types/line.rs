@@ -1,10 +1,20 @@
+use unicode_segmentation::UnicodeSegmentation;
+
 #[derive(Clone, Debug)]
 pub struct Line {
     line_no: usize,
     head_space: Option<String>,
     entered: Option<String>,
-    current: Option<char>,
+    current: Option<String>,
     rest: Option<String>,
+    composing: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Input {
+    Correct,
+    Composing,
+    Incorrect,
 }
 
 impl Line {
@@ -12,6 +22,29 @@ impl Line {
         line.len() - line.trim_start().len()
     }
 
+    fn split_first(text: &str) -> (Option<String>, Option<String>) {
+        match text.graphemes(true).next() {
+            Some(h) => (Some(h.to_string()), Some(text[h.len()..].to_string())),
+            None => (None, None),
+        }
+    }
+
+    // Graphemes that can not be entered from a keyboard or an IME, such as emoji,
+    // arrows or box drawing, are typed automatically.
+    fn is_typeable(grapheme: &str) -> bool {
+        match grapheme.chars().next() {
+            Some(c) => {
+                c.is_ascii_graphic()
+                    || c == ' '
+                    || c.is_alphanumeric()
+                    || ('\u{a1}'..='\u{ff}').contains(&c)
+                    || ('\u{3000}'..='\u{303f}').contains(&c)
+                    || ('\u{ff01}'..='\u{ff60}').contains(&c)
+            }
+            None => false,
+        }
+    }
+
     pub fn new(line_no: usize, line: &str) -> Self {
         let text = &line[Line::start_index(line)..];
         let head_space = &line[0..Line::start_index(line)];
@@ -21,33 +54,29 @@ impl Line {
             Some(head_space.to_string())
         };
 
-        match text.chars().collect::<Vec<char>>().as_slice() {
-            [h] => Line {
+        match Line::split_first(text) {
+            (Some(current), rest) => Line {
                 line_no,
                 head_space,
                 entered: None,
-                current: Some(*h),
-                rest: None,
-            },
-            [h, rest @ ..] => Line {
-                line_no,
-                head_space,
-                entered: None,
-                current: Some(*h),
-                rest: Some(String::from_iter(rest)),
-            },
+                current: Some(current),
+                rest,
+                composing: String::new(),
+            }
+            .skip_untypeable(),
             _ => Line {
                 line_no,
                 head_space: None,
                 entered: None,
                 current: None,
                 rest: None,
+                composing: String::new(),
             },
         }
     }
 
-    pub fn current_text(&self) -> Option<char> {
-        self.current
+    pub fn current_text(&self) -> Option<String> {
+        self.current.clone()
     }
 
     pub fn entered_text(&self) -> Option<String> {
@@ -63,10 +92,38 @@ impl Line {
         self.rest.clone()
     }
 
-    pub fn input(&self, c: char) -> bool {
-        match self.current {
-            Some(i) => i == c,
-            None => true,
+    pub fn composing_text(&self) -> &str {
+        &self.composing
+    }
+
+    pub fn input(&self, c: char) -> Input {
+        match &self.current {
+            Some(current) => {
+                let input = format!("{}{}", self.composing, c);
+
+                if *current == input {
+                    Input::Correct
+                } else if current.starts_with(&input) {
+                    Input::Composing
+                } else {
+                    Input::Incorrect
+                }
+            }
+            None => Input::Correct,
+        }
+    }
+
+    pub fn compose(&self, c: char) -> Self {
+        Line {
+            composing: format!("{}{}", self.composing, c),
+            ..self.clone()
+        }
+    }
+
+    pub fn cancel_composing(&self) -> Self {
+        Line {
+            composing: String::new(),
+            ..self.clone()
         }
     }
 
@@ -80,32 +137,32 @@ impl Line {
 
     pub fn next(&self) -> Self {
         if let Some(rest) = self.rest.clone() {
-            match rest.chars().collect::<Vec<char>>().as_slice() {
-                [h, rest @ ..] => Line {
-                    line_no: self.line_no,
-                    head_space: self.head_space.clone(),
-                    entered: match self.entered.clone() {
-                        Some(e) => self.current.map(|c| e + String::from(c).as_str()),
-                        None => self.current.map(String::from),
-                    },
-                    current: Some(*h),
-                    rest: Some(String::from_iter(rest)),
-                },
-                _ => Line {
-                    line_no: self.line_no,
-                    head_space: self.head_space.clone(),
-                    entered: match self.entered.clone() {
-                        Some(e) => self.current.map(|c| e + String::from(c).as_str()),
-                        None => self.current.map(String::from),
-                    },
-                    current: None,
-                    rest: None,
-                },
+            let entered = match self.entered.clone() {
+                Some(e) => self.current.clone().map(|c| e + &c),
+                None => self.current.clone(),
+            };
+            let (current, rest) = Line::split_first(&rest);
+
+            Line {
+                line_no: self.line_no,
+                head_space: self.head_space.clone(),
+                entered,
+                current,
+                rest,
+                composing: String::new(),
             }
+            .skip_untypeable()
         } else {
             self.clone()
         }
     }
+
+    fn skip_untypeable(self) -> Self {
+        match &self.current {
+            Some(current) if !Line::is_typeable(current) && self.rest.is_some() => self.next(),
+            _ => self,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -121,14 +178,7 @@ mod tests {
     #[test]
     fn has_next() {
         let input = Line::new(1, "      input test");
-        assert_eq!(
-            input
-                .clone()
-                .current_text()
-                .map(String::from)
-                .unwrap_or("".to_owned()),
-            "i"
-        );
+        assert_eq!(input.clone().current_text().unwrap_or("".to_owned()), "i");
 
         let next_input = input.next();
 
@@ -136,13 +186,7 @@ mod tests {
             &next_input.head_space.clone().unwrap(),
             &"      ".to_string()
         );
-        assert_eq!(
-            &next_input
-                .current_text()
-                .map(String::from)
-                .unwrap_or("".to_owned()),
-            &"n"
-        );
+        assert_eq!(&next_input.current_text().unwrap_or("".to_owned()), &"n");
         assert_eq!(&next_input.entered_text().unwrap(), &"      i".to_string());
         assert_eq!(&next_input.rest_text().unwrap(), &"put test".to_string());
     }
@@ -150,14 +194,7 @@ mod tests {
     #[test]
     fn no_next() {
         let input = Line::new(0, "i");
-        assert_eq!(
-            input
-                .clone()
-                .current_text()
-                .map(String::from)
-                .unwrap_or("".to_owned()),
-            "i"
-        );
+        assert_eq!(input.clone().current_text().unwrap_or("".to_owned()), "i");
         let next_input = input.next();
         assert!(next_input.is_entered());
     }
@@ -165,15 +202,54 @@ mod tests {
     #[test]
     fn new_line_only() {
         let input = Line::new(0, "\n");
-        assert_eq!(
-            input
-                .clone()
-                .current_text()
-                .map(String::from)
-                .unwrap_or("".to_owned()),
-            ""
-        );
+        assert_eq!(input.clone().current_text().unwrap_or("".to_owned()), "");
         let next_input = input.next();
         assert!(next_input.is_entered());
     }
+
+    #[test]
+    fn unicode() {
+        let input = Line::new(1, "// 日本語 é");
+        let input = input.next().next().next();
+        assert_eq!(input.current_text().unwrap(), "日");
+        assert_eq!(input.input('日'), Input::Correct);
+        assert_eq!(input.input('本'), Input::Incorrect);
+
+        let input = input.next().next().next().next();
+        assert_eq!(input.current_text().unwrap(), "é");
+        assert_eq!(input.input('é'), Input::Correct);
+    }
+
+    #[test]
+    fn composing() {
+        let input = Line::new(1, "e\u{301}x");
+        assert_eq!(input.current_text().unwrap(), "e\u{301}");
+        assert_eq!(input.input('e'), Input::Composing);
+
+        let input = input.compose('e');
+        assert_eq!(input.composing_text(), "e");
+        assert_eq!(input.input('\u{301}'), Input::Correct);
+        assert_eq!(input.input('x'), Input::Incorrect);
+        assert_eq!(input.cancel_composing().composing_text(), "");
+        assert_eq!(input.next().composing_text(), "");
+    }
+
+    #[test]
+    fn skip_untypeable() {
+        let input = Line::new(1, "a → b 🎉");
+        let input = input.next().next();
+        assert_eq!(input.entered_text().unwrap(), "a →");
+        assert_eq!(input.current_text().unwrap(), " ");
+
+        let input = input.next().next();
+        assert_eq!(input.current_text().unwrap(), " ");
+        let input = input.next();
+        assert_eq!(input.entered_text().unwrap(), "a → b 🎉");
+        assert_eq!(input.current_text(), None);
+        assert!(input.is_entered());
+
+        let input = Line::new(1, "🎉");
+        assert_eq!(input.entered_text().unwrap(), "🎉");
+        assert!(input.is_entered());
+    }
 }
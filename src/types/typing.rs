@@ -1,30 +1,45 @@
-use crate::types::line::Line;
+use crate::types::clock::Clock;
+use crate::types::keystroke::Keystroke;
+use crate::types::line::{Input, Line};
 use anyhow::{anyhow, Result};
 use std::cmp;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 #[derive(Clone, Debug)]
 pub enum Typing {
     BeforeStart(State),
     Running(State),
+    Paused(State),
     Finish(State),
 }
 
 #[derive(Clone, Debug)]
 pub struct State {
+    clock: Clock,
     current_index: usize,
     display_lines: usize,
-    end_time: Option<std::time::Instant>,
+    elapsed: Duration, // counted by ticks
+    end_time: Option<Duration>,
     current_error: usize, // number of character to delete before continuing
+    force_correction: bool,
+    keystrokes: Vec<Keystroke>,
+    last_input: Option<Duration>,
     lines: Vec<Line>,
+    paused_at: Option<Duration>,
     remaining_time: Duration,
-    start_time: Option<std::time::Instant>,
+    start_time: Option<Duration>,
+    timed: bool, // finish when remaining_time runs out
     typed: usize,
     typo: usize,
 }
 
 impl Typing {
-    pub fn new(text: &str, remaining_time: Duration, display_lines: usize) -> Result<Self> {
+    pub fn new(
+        text: &str,
+        remaining_time: Duration,
+        display_lines: usize,
+        force_correction: bool,
+    ) -> Result<Self> {
         if text.is_empty() {
             Err(anyhow!("text is empty"))
         } else {
@@ -34,14 +49,21 @@ impl Typing {
                 .map(|(i, v)| Line::new(i + 1, v))
                 .collect();
             Ok(Typing::BeforeStart(State {
+                clock: Clock::default(),
                 current_index: 0,
                 lines: lines.clone(),
                 start_time: None,
                 end_time: None,
+                paused_at: None,
+                elapsed: Duration::ZERO,
                 remaining_time,
+                timed: true,
                 typed: 0,
                 typo: 0,
                 current_error: 0,
+                force_correction,
+                keystrokes: Vec::new(),
+                last_input: None,
                 display_lines,
             }))
         }
@@ -49,28 +71,67 @@ impl Typing {
 
     pub fn restart(&self, text: &str, remaining_time: Duration) -> Self {
         match self {
-            Typing::Finish(s) => Typing::BeforeStart(State {
-                current_index: 0,
-                lines: Typing::to_lines(text),
-                start_time: None,
-                end_time: None,
-                remaining_time,
-                typed: 0,
-                typo: 0,
-                ..s.clone()
-            }),
-            Typing::Running(s) => Typing::Running(s.clone()),
-            Typing::BeforeStart(s) => Typing::BeforeStart(s.clone()),
+            Typing::Finish(_) => self.reset(text, remaining_time),
+            _ => self.clone(),
         }
     }
 
+    // Starts over with a new text, keeping the settings of the session.
+    pub fn reset(&self, text: &str, remaining_time: Duration) -> Self {
+        Typing::BeforeStart(State {
+            current_index: 0,
+            lines: Typing::to_lines(text),
+            start_time: None,
+            end_time: None,
+            paused_at: None,
+            elapsed: Duration::ZERO,
+            remaining_time,
+            typed: 0,
+            typo: 0,
+            current_error: 0,
+            keystrokes: Vec::new(),
+            last_input: None,
+            ..self.state().clone()
+        })
+    }
+
+    pub fn with_clock(&self, clock: Clock) -> Self {
+        self.map(|t| State { clock, ..t })
+    }
+
+    pub fn with_time_limit(&self, timed: bool) -> Self {
+        self.map(|t| State { timed, ..t })
+    }
+
+    fn state(&self) -> &State {
+        match self {
+            Typing::BeforeStart(t) | Typing::Running(t) | Typing::Paused(t) | Typing::Finish(t) => {
+                t
+            }
+        }
+    }
+
+    fn map(&self, f: impl FnOnce(State) -> State) -> Self {
+        match self.clone() {
+            Typing::BeforeStart(t) => Typing::BeforeStart(f(t)),
+            Typing::Running(t) => Typing::Running(f(t)),
+            Typing::Paused(t) => Typing::Paused(f(t)),
+            Typing::Finish(t) => Typing::Finish(f(t)),
+        }
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.state().clock.clone()
+    }
+
     pub fn start(&self) -> Self {
         match self {
             Typing::BeforeStart(s) => Typing::Running(State {
-                start_time: Some(Instant::now()),
+                start_time: Some(s.clock.now()),
                 ..s.clone()
             }),
             Typing::Running(t) => Typing::Running(t.clone()),
+            Typing::Paused(t) => Typing::Paused(t.clone()),
             Typing::Finish(t) => Typing::Finish(t.clone()),
         }
     }
@@ -78,7 +139,12 @@ impl Typing {
     pub fn finish(&self) -> Self {
         match self.clone() {
             Typing::Running(t) => Typing::Finish(State {
-                end_time: Some(Instant::now()),
+                end_time: Some(t.clock.now()),
+                ..t.clone()
+            }),
+            Typing::Paused(t) => Typing::Finish(State {
+                end_time: t.paused_at,
+                paused_at: None,
                 ..t.clone()
             }),
             Typing::BeforeStart(lines) => Typing::BeforeStart(lines),
@@ -86,57 +152,89 @@ impl Typing {
         }
     }
 
+    pub fn pause(&self) -> Self {
+        match self {
+            Typing::Running(t) => Typing::Paused(State {
+                paused_at: Some(t.clock.now()),
+                ..t.clone()
+            }),
+            _ => self.clone(),
+        }
+    }
+
+    // Shifts the start of the session by the paused time, so that it counts neither
+    // towards wpm nor towards the latency of the next keystroke.
+    pub fn resume(&self) -> Self {
+        match self {
+            Typing::Paused(t) => {
+                let paused = t
+                    .paused_at
+                    .map(|p| t.clock.now().saturating_sub(p))
+                    .unwrap_or_default();
+                Typing::Running(State {
+                    start_time: t.start_time.map(|s| s + paused),
+                    last_input: t.last_input.map(|l| l + paused),
+                    paused_at: None,
+                    ..t.clone()
+                })
+            }
+            _ => self.clone(),
+        }
+    }
+
     pub fn input(&self, c: char) -> Self {
         match self {
             Typing::Running(t) => {
                 let current_line = t.current();
-                let entered = current_line.input(c);
-                let mut lines = t.lines.clone();
-
-                if t.current_error > 0 {
-                    Typing::Running(State {
-                        lines,
-                        typed: t.typed,
+                let input = current_line.input(c);
+                let t = &t.record(Keystroke::new(
+                    &current_line.current_text().unwrap_or("\n".to_owned()),
+                    c,
+                    t.since_last_input().as_millis() as u64,
+                    t.current_error == 0 && input != Input::Incorrect,
+                ));
+
+                if t.force_correction && t.current_error > 0 {
+                    return Typing::Running(State {
                         current_error: t.current_error + 1,
                         ..t.clone()
-                    })
-                } else if entered {
-                    let next = current_line.next();
+                    });
+                }
 
-                    if next.is_entered() {
-                        self.next()
-                    } else {
-                        lines[t.current_index] = next;
+                match input {
+                    Input::Correct => Typing::advance(t, t.typed + 1, t.typo),
+                    Input::Composing => {
+                        let mut lines = t.lines.clone();
+                        lines[t.current_index] = current_line.compose(c);
+                        Typing::Running(State { lines, ..t.clone() })
+                    }
+                    Input::Incorrect if t.force_correction => {
+                        let mut lines = t.lines.clone();
+                        lines[t.current_index] = current_line.cancel_composing();
                         Typing::Running(State {
                             lines,
-                            typed: t.typed + 1,
+                            typo: t.typo + 1,
+                            current_error: t.current_error + 1,
                             ..t.clone()
                         })
                     }
-                } else {
-                    Typing::Running(State {
-                        lines,
-                        typed: t.typed,
-                        typo: t.typo + 1,
-                        current_error: t.current_error + 1,
-                        ..t.clone()
-                    })
+                    Input::Incorrect => Typing::advance(t, t.typed, t.typo + 1),
                 }
             }
             Typing::BeforeStart(t) => Typing::BeforeStart(t.clone()),
+            Typing::Paused(t) => Typing::Paused(t.clone()),
             Typing::Finish(t) => Typing::Finish(t.clone()),
         }
     }
 
     pub fn backspace(&self) -> Self {
         match self {
-            Typing::Running(t) => {
-                Typing::Running(State {
-                    current_error: t.current_error.saturating_sub(1),
-                    ..t.clone()
-                })
-            }
+            Typing::Running(t) => Typing::Running(State {
+                current_error: t.current_error.saturating_sub(1),
+                ..t.clone()
+            }),
             Typing::BeforeStart(t) => Typing::BeforeStart(t.clone()),
+            Typing::Paused(t) => Typing::Paused(t.clone()),
             Typing::Finish(t) => Typing::Finish(t.clone()),
         }
     }
@@ -154,6 +252,7 @@ impl Typing {
                     self.finish()
                 }
             }
+            Typing::Paused(t) => Typing::Paused(t.clone()),
             Typing::Finish(t) => Typing::Finish(t.clone()),
             Typing::BeforeStart(t) => Typing::BeforeStart(t.clone()),
         }
@@ -162,6 +261,7 @@ impl Typing {
     pub fn display_lines(&self) -> Vec<Line> {
         match self.clone() {
             Typing::Running(t) => t.display_lines(),
+            Typing::Paused(t) => t.display_lines(),
             Typing::Finish(t) => t.display_lines(),
             Typing::BeforeStart(t) => t.display_lines(),
         }
@@ -175,9 +275,13 @@ impl Typing {
         matches!(self, Typing::BeforeStart(_))
     }
 
+    pub fn is_paused(&self) -> bool {
+        matches!(self, Typing::Paused(_))
+    }
+
     pub fn wpm(&self) -> usize {
         match self {
-            Typing::Running(s) => s.wpm(),
+            Typing::Running(s) | Typing::Paused(s) => s.wpm(),
             Typing::Finish(s) => s.wpm(),
             _ => 0,
         }
@@ -185,7 +289,7 @@ impl Typing {
 
     pub fn acc(&self) -> usize {
         match self {
-            Typing::Running(s) => s.acc(),
+            Typing::Running(s) | Typing::Paused(s) => s.acc(),
             Typing::Finish(s) => s.acc(),
             _ => 0,
         }
@@ -193,7 +297,7 @@ impl Typing {
 
     pub fn typed(&self) -> usize {
         match self {
-            Typing::Running(s) => s.typed,
+            Typing::Running(s) | Typing::Paused(s) => s.typed,
             Typing::Finish(s) => s.typed,
             _ => 0,
         }
@@ -201,14 +305,26 @@ impl Typing {
 
     pub fn typo(&self) -> usize {
         match self {
-            Typing::Running(s) => s.typo,
+            Typing::Running(s) | Typing::Paused(s) => s.typo,
             Typing::Finish(s) => s.typo,
             _ => 0,
         }
     }
 
+    pub fn keystrokes(&self) -> Vec<Keystroke> {
+        match self {
+            Typing::Running(s) | Typing::Paused(s) => s.keystrokes.clone(),
+            Typing::Finish(s) => s.keystrokes.clone(),
+            _ => Vec::new(),
+        }
+    }
+
     pub fn tick(&self) -> Self {
         match self {
+            Typing::Running(t) if !t.timed => Typing::Running(State {
+                elapsed: t.elapsed + Duration::from_secs(1),
+                ..t.clone()
+            }),
             Typing::Running(t) => {
                 if t.remaining_time == Duration::from_secs(0) {
                     self.finish()
@@ -221,18 +337,29 @@ impl Typing {
                         } else {
                             t.remaining_time - Duration::from_secs(1)
                         },
+                        elapsed: t.elapsed + Duration::from_secs(1),
                         ..t.clone()
                     })
                 }
             }
+            Typing::Paused(t) => Typing::Paused(t.clone()),
             Typing::Finish(t) => Typing::Finish(t.clone()),
             Typing::BeforeStart(t) => Typing::BeforeStart(t.clone()),
         }
     }
 
+    pub fn elapsed(&self) -> Duration {
+        self.state().elapsed
+    }
+
+    pub fn is_timed(&self) -> bool {
+        self.state().timed
+    }
+
     pub fn get_remaining_time(&self) -> usize {
         match self.clone() {
             Typing::Running(t) => t.remaining_time.as_secs() as usize,
+            Typing::Paused(t) => t.remaining_time.as_secs() as usize,
             Typing::Finish(t) => t.remaining_time.as_secs() as usize,
             Typing::BeforeStart(t) => t.remaining_time.as_secs() as usize,
         }
@@ -244,6 +371,10 @@ impl Typing {
                 t.remaining_time = time;
                 Typing::Running(t)
             }
+            Typing::Paused(mut t) => {
+                t.remaining_time = time;
+                Typing::Paused(t)
+            }
             Typing::Finish(mut t) => {
                 t.remaining_time = time;
                 Typing::Finish(t)
@@ -257,20 +388,46 @@ impl Typing {
 
     pub fn is_error(&self) -> bool {
         match self {
-            Typing::Running(s) => s.current_error > 0,
+            Typing::Running(s) | Typing::Paused(s) => s.current_error > 0,
             Typing::Finish(_) => false,
             _ => false,
         }
     }
 
+    pub fn line_count(&self) -> usize {
+        self.state().lines.len()
+    }
+
     pub fn current_line_index(&self) -> usize {
         match self {
-            Typing::Running(s) => s.current_index,
+            Typing::Running(s) | Typing::Paused(s) => s.current_index,
             Typing::Finish(s) => s.current_index,
             _ => 0,
         }
     }
 
+    fn advance(t: &State, typed: usize, typo: usize) -> Self {
+        let next = t.current().next();
+
+        if next.is_entered() {
+            Typing::Running(State {
+                typed,
+                typo,
+                ..t.clone()
+            })
+            .next()
+        } else {
+            let mut lines = t.lines.clone();
+            lines[t.current_index] = next;
+            Typing::Running(State {
+                lines,
+                typed,
+                typo,
+                ..t.clone()
+            })
+        }
+    }
+
     fn to_lines(text: &str) -> Vec<Line> {
         text.split('\n')
             .enumerate()
@@ -281,9 +438,28 @@ impl Typing {
 
 impl State {
     pub fn running_time(&self) -> Duration {
+        let now = self.clock.now();
         self.end_time
-            .unwrap_or(Instant::now())
-            .duration_since(self.start_time.unwrap_or(Instant::now()))
+            .or(self.paused_at)
+            .unwrap_or(now)
+            .saturating_sub(self.start_time.unwrap_or(now))
+    }
+
+    fn since_last_input(&self) -> Duration {
+        self.last_input
+            .or(self.start_time)
+            .map(|t| self.clock.now().saturating_sub(t))
+            .unwrap_or_default()
+    }
+
+    fn record(&self, keystroke: Keystroke) -> Self {
+        let mut keystrokes = self.keystrokes.clone();
+        keystrokes.push(keystroke);
+        State {
+            keystrokes,
+            last_input: Some(self.clock.now()),
+            ..self.clone()
+        }
     }
 
     pub fn display_lines(&self) -> Vec<Line> {
@@ -321,18 +497,19 @@ mod tests {
 
     #[test]
     fn multi_lines() {
-        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10);
+        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10, false);
         let typing = typing.unwrap().start();
         assert_eq!(typing.display_lines().len(), 2);
 
-        let typing = Typing::new("    line1\n  line2\n line3\n line4\n line5\n line6\n line7\n line8\n line9\n line10\n line11", Duration::from_secs(10), 10);
+        let typing = Typing::new("    line1\n  line2\n line3\n line4\n line5\n line6\n line7\n line8\n line9\n line10\n line11", Duration::from_secs(10), 10, false);
         let typing = typing.unwrap().start();
         assert_eq!(typing.display_lines().len(), 10);
     }
 
     #[test]
+    #[allow(clippy::single_match)]
     fn next_line() {
-        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10);
+        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10, false);
         let typing = typing.unwrap().start();
         let next = typing.next();
 
@@ -346,7 +523,7 @@ mod tests {
 
     #[test]
     fn next_empty() {
-        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10);
+        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10, false);
         let typing = typing.unwrap().start();
         let next = typing.next().next();
         assert!(next.is_finish());
@@ -354,12 +531,12 @@ mod tests {
 
     #[test]
     fn empty_lines() {
-        assert!(Typing::new("", Duration::from_secs(0), 10).is_err());
+        assert!(Typing::new("", Duration::from_secs(0), 10, false).is_err());
     }
 
     #[test]
     fn wpm() {
-        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10);
+        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10, false);
         let typing = typing.unwrap().start();
         let typing = typing.input('l');
         let typing = typing.input('i');
@@ -370,17 +547,152 @@ mod tests {
         assert_eq!(typing.wpm(), 48);
     }
 
+    #[test]
+    fn clock() {
+        let clock = Clock::manual();
+        let typing = Typing::new("abc", Duration::from_secs(10), 10, false).unwrap();
+        let typing = typing.with_clock(clock.clone()).start();
+        clock.advance(Duration::from_millis(250));
+        let typing = typing.input('a');
+        clock.advance(Duration::from_millis(1750));
+        let typing = typing.input('b').finish();
+
+        let latencies: Vec<u64> = typing.keystrokes().iter().map(|k| k.latency).collect();
+        assert_eq!(latencies, vec![250, 1750]);
+        assert_eq!(typing.wpm(), 12);
+    }
+
+    #[test]
+    fn pause() {
+        let clock = Clock::manual();
+        let typing = Typing::new("abc", Duration::from_secs(10), 10, false).unwrap();
+        let typing = typing.with_clock(clock.clone()).start();
+        clock.advance(Duration::from_secs(1));
+        let typing = typing.input('a').pause();
+        assert!(typing.is_paused());
+
+        clock.advance(Duration::from_secs(60));
+        let typing = typing.input('b').tick();
+        assert_eq!(typing.typed(), 1);
+        assert_eq!(typing.get_remaining_time(), 10);
+        let Typing::Paused(t) = &typing else {
+            panic!("the session is not paused");
+        };
+        assert_eq!(t.running_time(), Duration::from_secs(1));
+
+        let typing = typing.resume();
+        assert!(!typing.is_paused());
+        clock.advance(Duration::from_secs(1));
+        let typing = typing.input('b');
+        assert_eq!(typing.keystrokes()[1].latency, 1000);
+
+        let typing = typing.pause();
+        clock.advance(Duration::from_secs(30));
+        let typing = typing.finish();
+        assert!(typing.is_finish());
+        assert_eq!(typing.wpm(), 12);
+    }
+
+    #[test]
+    fn untimed() {
+        let typing = Typing::new("abc", Duration::from_secs(1), 10, false).unwrap();
+        let typing = typing.with_time_limit(false).start().tick().tick().tick();
+        assert!(!typing.is_finish());
+        assert_eq!(typing.elapsed(), Duration::from_secs(3));
+        assert_eq!(typing.get_remaining_time(), 1);
+
+        let typing = typing.input('a').input('b').input('c');
+        assert!(typing.is_finish());
+
+        let typing = typing.reset("de", Duration::from_secs(1));
+        assert!(typing.is_before_start());
+        assert!(!typing.is_timed());
+        assert_eq!(typing.elapsed(), Duration::ZERO);
+    }
+
     #[test]
     fn acc() {
-        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10);
+        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10, false);
         let typing = typing.unwrap().start();
         let typing = typing.input('l');
         let typing = typing.input('2');
-        let typing = typing.input('i');
         let typing = typing.input('n');
         let typing = typing.input('e');
+        let typing = typing.input('1');
         let typing = typing.finish();
 
         assert_eq!(typing.acc(), 80);
     }
+
+    #[test]
+    fn lenient_correction() {
+        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10, false);
+        let typing = typing.unwrap().start();
+        let typing = typing.input('x');
+
+        assert!(!typing.is_error());
+        assert_eq!(typing.typo(), 1);
+        match typing {
+            Typing::Running(line) => {
+                assert_eq!(line.current().current_text(), Some("i".to_string()));
+            }
+            _ => panic!("typing should be running"),
+        }
+    }
+
+    #[test]
+    fn force_correction() {
+        let typing = Typing::new("    line1\n  line2", Duration::from_secs(10), 10, true);
+        let typing = typing.unwrap().start();
+        let typing = typing.input('l');
+        let typing = typing.input('x');
+        let typing = typing.input('i');
+
+        assert!(typing.is_error());
+        assert_eq!(typing.typed(), 1);
+        assert_eq!(typing.typo(), 1);
+
+        let typing = typing.backspace();
+        assert!(typing.is_error());
+
+        let typing = typing.backspace();
+        assert!(!typing.is_error());
+
+        let typing = typing.input('i');
+        assert_eq!(typing.typed(), 2);
+        match typing {
+            Typing::Running(line) => {
+                assert_eq!(line.current().current_text(), Some("n".to_string()));
+            }
+            _ => panic!("typing should be running"),
+        }
+    }
+
+    #[test]
+    fn keystrokes() {
+        let typing = Typing::new("ab\nc", Duration::from_secs(10), 10, true);
+        let typing = typing.unwrap().start();
+        let typing = typing.input('a');
+        let typing = typing.input('x');
+        let typing = typing.input('y');
+        let typing = typing.backspace().backspace();
+        let typing = typing.input('b');
+        let typing = typing.input('c');
+
+        let keystrokes: Vec<(String, char, bool)> = typing
+            .keystrokes()
+            .into_iter()
+            .map(|k| (k.expected, k.actual, k.correct))
+            .collect();
+        assert_eq!(
+            keystrokes,
+            vec![
+                ("a".to_owned(), 'a', true),
+                ("b".to_owned(), 'x', false),
+                ("b".to_owned(), 'y', false),
+                ("b".to_owned(), 'b', true),
+                ("c".to_owned(), 'c', true),
+            ]
+        );
+    }
 }
@@ -8,40 +8,36 @@ use ratatui::{
 };
 use std::{cmp::Ordering, path::PathBuf};
 
-use crate::app::App;
+pub mod highlight;
+pub mod keyboard;
+pub mod picker;
+pub mod theme;
+
+use crate::app::{App, Mode};
+use crate::config::KeyBindings;
+use crate::types::keystroke::{KeyStat, KeyStats};
 use crate::types::line::Line;
 use crate::types::typing::Typing;
+use highlight::Highlight;
+use keyboard::{Heatmap, Keyboard};
+use theme::Theme;
 
-pub enum Theme {
-    Dark,
-    Light,
+pub struct Source {
+    pub path: PathBuf,
+    pub subject: Option<String>,
+    pub first_line: Option<usize>, // line number of the text in the file, shown when set
+    pub seed: Option<u64>,         // seed that chose the text, shown on the result
 }
 
-impl Theme {
-    pub fn new(theme: &str) -> Self {
-        match theme {
-            "dark" => Theme::Dark,
-            "light" => Theme::Light,
-            _ => Theme::Dark,
-        }
-    }
-
-    pub fn fg(&self) -> Color {
-        match self {
-            Theme::Dark => Color::White,
-            Theme::Light => Color::Black,
-        }
-    }
-
-    pub fn bg(&self) -> Color {
-        match self {
-            Theme::Dark => Color::Black,
-            Theme::Light => Color::White,
-        }
-    }
-}
-
-pub fn view(f: &mut Frame, app: &App, theme: &Theme, file: PathBuf) {
+pub fn view(
+    f: &mut Frame,
+    app: &App,
+    theme: &Theme,
+    highlight: &Highlight,
+    keyboard: &Keyboard,
+    source: &Source,
+    keys: &KeyBindings,
+) {
     if app.typing.is_finish() {
         let result = app.result();
         let chunks = Layout::default()
@@ -49,18 +45,23 @@ pub fn view(f: &mut Frame, app: &App, theme: &Theme, file: PathBuf) {
             .constraints(
                 [
                     Constraint::Percentage(10),
-                    Constraint::Percentage(70),
+                    Constraint::Min(5),
+                    Constraint::Length(5),
+                    Constraint::Length(7),
                     Constraint::Percentage(20),
                 ]
                 .as_ref(),
             )
             .split(f.area());
+        let stats = KeyStats::new(&result.keystrokes);
         f.render_widget(result_view(&app.typing, Borders::BOTTOM, theme), chunks[0]);
         f.render_widget(
             chart_view(app, &result.wpm_plot, &result.acc_plot, theme),
             chunks[1],
         );
-        f.render_widget(help_view(theme, file), chunks[2]);
+        f.render_widget(key_stats_view(&stats, theme), chunks[2]);
+        f.render_widget(Heatmap::new(keyboard, &stats, theme), chunks[3]);
+        f.render_widget(help_view(theme, source, keys, app.mode, false), chunks[4]);
     } else if app.typing.is_before_start() {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -79,11 +80,14 @@ pub fn view(f: &mut Frame, app: &App, theme: &Theme, file: PathBuf) {
                 app.typing.display_lines(),
                 app.typing.current_line_index(),
                 app.typing.is_error(),
+                app.typing.is_paused(),
+                gutter(app, source),
                 theme,
+                highlight,
             ),
             chunks[1],
         );
-        f.render_widget(help_view(theme, file), chunks[2]);
+        f.render_widget(help_view(theme, source, keys, app.mode, true), chunks[2]);
     } else {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -96,13 +100,16 @@ pub fn view(f: &mut Frame, app: &App, theme: &Theme, file: PathBuf) {
                 .as_ref(),
             )
             .split(f.area());
-        f.render_widget(remaining_time_view(&app.typing, theme), chunks[0]);
+        f.render_widget(remaining_time_view(app, theme), chunks[0]);
         f.render_widget(
             lines(
                 app.typing.display_lines(),
                 app.typing.current_line_index(),
                 app.typing.is_error(),
+                app.typing.is_paused(),
+                gutter(app, source),
                 theme,
+                highlight,
             ),
             chunks[1],
         );
@@ -124,25 +131,25 @@ pub fn chart_view<'a>(
             .name("wpm")
             .marker(symbols::Marker::Dot)
             .graph_type(GraphType::Line)
-            .style(Style::default().bg(theme.bg()).fg(Color::Yellow))
+            .style(Style::default().bg(theme.bg()).fg(theme.chart_wpm()))
             .data(wpm_dataset),
         Dataset::default()
             .name("acc")
             .marker(symbols::Marker::Dot)
             .graph_type(GraphType::Line)
-            .style(Style::default().bg(theme.bg()).fg(Color::DarkGray))
+            .style(Style::default().bg(theme.bg()).fg(theme.chart_acc()))
             .data(acc_dataset),
     ])
     .style(Style::default().bg(theme.bg()).fg(theme.fg()))
     .block(Block::default().style(Style::default().bg(theme.bg()).fg(theme.fg())))
     .x_axis(
         Axis::default()
-            .style(Style::default().bg(theme.bg()).fg(Color::DarkGray))
+            .style(Style::default().bg(theme.bg()).fg(theme.muted()))
             .labels(vec![
-                Span::styled("0", Style::default().fg(Color::DarkGray)),
+                Span::styled("0", Style::default().fg(theme.muted())),
                 Span::styled(
                     (elapsed_time.as_secs() / 2).to_string(),
-                    Style::default().fg(Color::DarkGray),
+                    Style::default().fg(theme.muted()),
                 ),
                 Span::styled(
                     elapsed_time.as_secs().to_string(),
@@ -155,63 +162,87 @@ pub fn chart_view<'a>(
         Axis::default()
             .style(Style::default().bg(theme.bg()).fg(theme.fg()))
             .labels(vec![
-                Span::styled("0", Style::default().fg(Color::DarkGray)),
+                Span::styled("0", Style::default().fg(theme.muted())),
                 Span::styled(
                     (result.wpm_max / 2.0).floor().to_string(),
-                    Style::default().bg(theme.bg()).fg(Color::DarkGray),
+                    Style::default().bg(theme.bg()).fg(theme.muted()),
                 ),
                 Span::styled(
                     result.wpm_max.to_string(),
-                    Style::default().bg(theme.bg()).fg(Color::DarkGray),
+                    Style::default().bg(theme.bg()).fg(theme.muted()),
                 ),
             ])
             .bounds([0.0, result.wpm_max]),
     )
 }
 
-fn help_view<'a>(theme: &Theme, path: PathBuf) -> Paragraph<'a> {
-    let file_path = ratatui::text::Line::from(Span::styled(
-        path.into_os_string().into_string().unwrap(),
-        Style::default().bg(theme.bg()).fg(Color::DarkGray),
-    ));
-    let help = ratatui::text::Line::from(vec![
-        Span::styled(
-            "r",
-            Style::default()
-                .bg(theme.bg())
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            " to restart",
-            Style::default().bg(theme.bg()).fg(Color::DarkGray),
-        ),
-        Span::styled(", ", Style::default().bg(theme.bg()).fg(Color::DarkGray)),
-        Span::styled(
-            "q",
-            Style::default()
-                .bg(theme.bg())
-                .fg(Color::Red)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            " to quit",
-            Style::default().bg(theme.bg()).fg(Color::DarkGray),
-        ),
-        Span::styled(", ", Style::default().bg(theme.bg()).fg(Color::DarkGray)),
-        Span::styled(
-            "left, right",
-            Style::default()
-                .bg(theme.bg())
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(
-            " to select a time",
-            Style::default().bg(theme.bg()).fg(Color::DarkGray),
-        ),
-    ]);
-    Paragraph::new(vec![help, file_path])
+// `pause` shows the key pausing the session, before it starts.
+fn help_view<'a>(
+    theme: &Theme,
+    source: &Source,
+    keys: &KeyBindings,
+    mode: Mode,
+    before_start: bool,
+) -> Paragraph<'a> {
+    let mut file_path = vec![Span::styled(
+        source.path.to_string_lossy().to_string(),
+        Style::default().bg(theme.bg()).fg(theme.muted()),
+    )];
+    if let Some(seed) = source.seed {
+        file_path.push(Span::styled(
+            "  seed: ",
+            Style::default().bg(theme.bg()).fg(theme.muted()),
+        ));
+        file_path.push(Span::styled(
+            seed.to_string(),
+            Style::default().bg(theme.bg()).fg(theme.fg()),
+        ));
+    }
+    let file_path = ratatui::text::Line::from(file_path);
+    let subject = source.subject.clone().map(|s| {
+        ratatui::text::Line::from(Span::styled(
+            s,
+            Style::default().bg(theme.bg()).fg(theme.fg()),
+        ))
+    });
+    let mut items = vec![
+        (keys.restart.to_string(), " to restart"),
+        (keys.quit.to_string(), " to quit"),
+    ];
+    // pausing and the presets only apply before the session is finished
+    if before_start {
+        items.push((format!("ctrl-{}", keys.pause), " to pause"));
+        match mode {
+            Mode::Time => items.push(("left, right".to_owned(), " to select a time")),
+            Mode::Words(_) => items.push(("left, right".to_owned(), " to select a word count")),
+            Mode::Lines(_) => items.push(("left, right".to_owned(), " to select a line count")),
+            Mode::Zen => (),
+        }
+    }
+    let help: Vec<Span> = items
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, (key, text))| {
+            [
+                (i > 0)
+                    .then(|| Span::styled(", ", Style::default().bg(theme.bg()).fg(theme.muted()))),
+                Some(Span::styled(
+                    key,
+                    Style::default()
+                        .bg(theme.bg())
+                        .fg(theme.help_key())
+                        .add_modifier(Modifier::BOLD),
+                )),
+                Some(Span::styled(
+                    text,
+                    Style::default().bg(theme.bg()).fg(theme.muted()),
+                )),
+            ]
+        })
+        .flatten()
+        .collect();
+    let help = ratatui::text::Line::from(help);
+    Paragraph::new([vec![help], subject.into_iter().collect(), vec![file_path]].concat())
         .style(Style::default().bg(theme.bg()).fg(theme.fg()))
         .block(
             Block::default()
@@ -221,14 +252,37 @@ fn help_view<'a>(theme: &Theme, path: PathBuf) -> Paragraph<'a> {
         .alignment(Alignment::Left)
 }
 
-fn remaining_time_view<'a>(typing: &Typing, theme: &Theme) -> Paragraph<'a> {
-    let time = ratatui::text::Line::from(vec![Span::styled(
-        typing.get_remaining_time().to_string(),
+fn remaining_time_view<'a>(app: &App, theme: &Theme) -> Paragraph<'a> {
+    let typing = &app.typing;
+    let time = if typing.is_timed() {
+        typing.get_remaining_time()
+    } else {
+        app.elapsed_time().as_secs() as usize
+    };
+    let mut time = vec![Span::styled(
+        time.to_string(),
         Style::default()
             .bg(theme.bg())
-            .fg(Color::Green)
+            .fg(theme.entered())
             .add_modifier(Modifier::BOLD),
-    )]);
+    )];
+    if app.mode == Mode::Zen {
+        time.push(Span::styled(
+            format!(
+                " {}/{} lines",
+                typing.current_line_index(),
+                typing.line_count()
+            ),
+            Style::default().bg(theme.bg()).fg(theme.muted()),
+        ));
+    }
+    if typing.is_paused() {
+        time.push(Span::styled(
+            " paused",
+            Style::default().bg(theme.bg()).fg(theme.muted()),
+        ));
+    }
+    let time = ratatui::text::Line::from(time);
     Paragraph::new(time)
         .style(Style::default().bg(theme.bg()).fg(theme.fg()))
         .alignment(Alignment::Left)
@@ -236,83 +290,177 @@ fn remaining_time_view<'a>(typing: &Typing, theme: &Theme) -> Paragraph<'a> {
 
 fn result_view<'a>(typing: &Typing, border: Borders, theme: &Theme) -> Paragraph<'a> {
     let result = ratatui::text::Line::from(vec![
-        Span::styled(
-            "wpm: ",
-            Style::default().bg(Theme::bg(theme)).fg(Color::DarkGray),
-        ),
+        Span::styled("wpm: ", Style::default().bg(theme.bg()).fg(theme.muted())),
         Span::styled(
             typing.wpm().to_string(),
-            Style::default().bg(Theme::bg(theme)).fg(Color::Yellow),
-        ),
-        Span::styled(
-            " acc: ",
-            Style::default().bg(Theme::bg(theme)).fg(Color::DarkGray),
+            Style::default().bg(theme.bg()).fg(theme.chart_wpm()),
         ),
+        Span::styled(" acc: ", Style::default().bg(theme.bg()).fg(theme.muted())),
         Span::styled(
             typing.acc().to_string() + "%",
-            Style::default().bg(Theme::bg(theme)).fg(Color::Gray),
-        ),
-        Span::styled(
-            " key: ",
-            Style::default().bg(Theme::bg(theme)).fg(Color::DarkGray),
+            Style::default().bg(theme.bg()).fg(theme.fg()),
         ),
+        Span::styled(" key: ", Style::default().bg(theme.bg()).fg(theme.muted())),
         Span::styled(
             (typing.typed() + typing.typo()).to_string(),
-            Style::default().bg(Theme::bg(theme)).fg(Color::Gray),
+            Style::default().bg(theme.bg()).fg(theme.fg()),
         ),
-        Span::styled("/", Style::default().bg(Theme::bg(theme)).fg(Color::Gray)),
+        Span::styled("/", Style::default().bg(theme.bg()).fg(theme.fg())),
         Span::styled(
             (typing.typo()).to_string(),
-            Style::default().bg(Theme::bg(theme)).fg(Color::Red),
+            Style::default().bg(theme.bg()).fg(theme.error()),
         ),
     ]);
     Paragraph::new(result)
-        .style(Style::default().bg(Theme::bg(theme)).fg(Theme::fg(theme)))
+        .style(Style::default().bg(theme.bg()).fg(theme.fg()))
         .block(
             Block::default()
                 .borders(border)
-                .style(Style::default().bg(Theme::bg(theme)).fg(Theme::fg(theme))),
+                .style(Style::default().bg(theme.bg()).fg(theme.fg())),
         )
         .alignment(Alignment::Left)
 }
 
-fn time_view<'a>(app: &App, theme: &Theme) -> Paragraph<'a> {
-    let times: Vec<Span> = app
-        .selectable_time()
-        .iter()
-        .map(|t| {
-            Span::styled(
-                format!("{} ", t.as_secs()),
-                if app.time == *t {
-                    Style::default()
-                        .bg(theme.bg())
-                        .fg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD)
+fn key_stats_view<'a>(stats: &KeyStats, theme: &Theme) -> Paragraph<'a> {
+    let row = |title: &str, keys: Vec<KeyStat>, error: bool| {
+        let mut spans = vec![Span::styled(
+            format!("{:<14}", title),
+            Style::default().bg(theme.bg()).fg(theme.muted()),
+        )];
+        for key in keys {
+            spans.push(Span::styled(
+                key_label(&key.key),
+                Style::default()
+                    .bg(theme.bg())
+                    .fg(theme.fg())
+                    .add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(
+                if error {
+                    format!(" {}/{}  ", key.errors, key.count)
                 } else {
-                    Style::default().bg(theme.bg()).fg(Color::DarkGray)
+                    format!(" {}ms  ", key.avg_latency())
                 },
-            )
-        })
-        .collect();
-    let result = ratatui::text::Line::from(times);
+                Style::default().bg(theme.bg()).fg(if error {
+                    theme.error()
+                } else {
+                    theme.chart_wpm()
+                }),
+            ));
+        }
+        ratatui::text::Line::from(spans)
+    };
+
+    Paragraph::new(vec![
+        row("slow keys", stats.slowest_keys(5), false),
+        row("typo keys", stats.error_prone_keys(5), true),
+        row("slow bigrams", stats.slowest_bigrams(5), false),
+        row("typo bigrams", stats.error_prone_bigrams(5), true),
+    ])
+    .style(Style::default().bg(theme.bg()).fg(theme.fg()))
+    .block(Block::default().style(Style::default().bg(theme.bg()).fg(theme.fg())))
+    .alignment(Alignment::Left)
+}
+
+fn key_label(key: &str) -> String {
+    key.replace(' ', "␣").replace('\n', "⏎")
+}
+
+fn time_view<'a>(app: &App, theme: &Theme) -> Paragraph<'a> {
+    let preset = |label: String, selected: bool| {
+        Span::styled(
+            label + " ",
+            if selected {
+                Style::default()
+                    .bg(theme.bg())
+                    .fg(theme.help_key())
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().bg(theme.bg()).fg(theme.muted())
+            },
+        )
+    };
+    let presets: Vec<Span> = match app.mode {
+        Mode::Zen => vec![preset(Mode::Zen.name().to_owned(), true)],
+        Mode::Time => app
+            .selectable_time()
+            .iter()
+            .map(|t| preset(t.as_secs().to_string(), app.time == *t))
+            .collect(),
+        mode => [Span::styled(
+            format!("{} ", mode.name()),
+            Style::default().bg(theme.bg()).fg(theme.muted()),
+        )]
+        .into_iter()
+        .chain(
+            app.selectable_count()
+                .into_iter()
+                .map(|n| preset(n.to_string(), mode.count() == Some(n))),
+        )
+        .collect(),
+    };
+    let result = ratatui::text::Line::from(presets);
     Paragraph::new(result)
         .alignment(Alignment::Left)
         .block(Block::default().style(Style::default().bg(theme.bg()).fg(theme.fg())))
 }
 
+// The first line number and the width of the line numbers.
+fn gutter(app: &App, source: &Source) -> Option<(usize, usize)> {
+    source.first_line.map(|first_line| {
+        (
+            first_line,
+            (first_line + app.typing.line_count().saturating_sub(1))
+                .to_string()
+                .len(),
+        )
+    })
+}
+
 fn lines<'a>(
     lines: Vec<Line>,
     current_line_index: usize,
     is_typing_error: bool,
+    is_paused: bool,
+    gutter: Option<(usize, usize)>,
     theme: &Theme,
+    highlight: &Highlight,
 ) -> Paragraph<'a> {
     let text: Vec<ratatui::text::Line<'a>> = lines
         .iter()
-        .map(|l| line(l.clone(), current_line_index, is_typing_error, theme))
+        .map(|l| {
+            let text = line(
+                l.clone(),
+                current_line_index,
+                is_typing_error,
+                theme,
+                highlight,
+            );
+            match gutter {
+                Some((first_line, width)) => ratatui::text::Line::from(
+                    [
+                        vec![Span::styled(
+                            format!("{:>width$} ", first_line + l.line_no() - 1, width = width),
+                            Style::default().bg(theme.bg()).fg(theme.muted()),
+                        )],
+                        text.spans,
+                    ]
+                    .concat(),
+                ),
+                None => text,
+            }
+        })
         .collect();
+    let style = Style::default().bg(theme.bg()).fg(theme.fg());
+    let style = if is_paused {
+        style.add_modifier(Modifier::DIM)
+    } else {
+        style
+    };
+
     Paragraph::new(text)
-        .style(Style::default().bg(theme.bg()).fg(theme.fg()))
-        .block(Block::default().style(Style::default().bg(theme.bg()).fg(theme.fg())))
+        .style(style)
+        .block(Block::default().style(style))
         .alignment(Alignment::Left)
 }
 
@@ -321,74 +469,204 @@ fn line<'a>(
     current_line_index: usize,
     is_typing_error: bool,
     theme: &Theme,
+    highlight: &Highlight,
 ) -> ratatui::text::Line<'a> {
+    let entered_text = line.entered_text().unwrap_or("".to_owned());
+    let current_text = line.current_text().unwrap_or("".to_owned());
+    let entered = Span::styled(
+        entered_text,
+        Style::default().bg(theme.bg()).fg(theme.entered()),
+    );
+
     match (line.line_no() - 1).cmp(&current_line_index) {
         Ordering::Equal => {
-            let entered = Span::styled(
-                line.entered_text().unwrap_or("".to_owned()),
-                Style::default().bg(theme.bg()).fg(Color::Green),
-            );
             let current = if is_typing_error {
                 Span::styled(
-                    line.current_text()
-                        .map(String::from)
-                        .unwrap_or("".to_owned()),
+                    current_text,
                     Style::default()
-                        .bg(Color::Red)
-                        .fg(Color::White)
+                        .bg(theme.error())
+                        .fg(theme.bg())
                         .add_modifier(Modifier::SLOW_BLINK),
                 )
+            } else if !line.composing_text().is_empty() {
+                Span::styled(
+                    current_text,
+                    Style::default()
+                        .bg(theme.current())
+                        .fg(theme.bg())
+                        .add_modifier(Modifier::BOLD)
+                        .add_modifier(Modifier::UNDERLINED),
+                )
             } else {
                 Span::styled(
-                    line.current_text()
-                        .map(String::from)
-                        .unwrap_or("".to_owned()),
+                    current_text,
                     Style::default()
-                        .bg(Color::Green)
-                        .fg(Color::White)
+                        .bg(theme.current())
+                        .fg(theme.bg())
                         .add_modifier(Modifier::BOLD)
                         .add_modifier(Modifier::SLOW_BLINK),
                 )
             };
-            let rest = Span::styled(
-                line.rest_text().unwrap_or("".to_owned()),
-                Style::default().bg(theme.bg()).fg(theme.fg()),
-            );
-            ratatui::text::Line::from(vec![entered, current, rest])
+            let rest = pending(&line, false, theme.fg(), theme, highlight);
+            ratatui::text::Line::from([vec![entered, current], rest].concat())
         }
         Ordering::Greater => {
-            let entered = Span::styled(
-                line.entered_text().unwrap_or("".to_owned()),
-                Style::default().bg(theme.bg()).fg(Color::Green),
-            );
-            let current = Span::styled(
-                line.current_text()
-                    .map(String::from)
-                    .unwrap_or("".to_owned()),
-                Style::default().bg(theme.bg()).fg(Color::DarkGray),
-            );
-            let rest = Span::styled(
-                line.rest_text().unwrap_or("".to_owned()),
-                Style::default().bg(theme.bg()).fg(Color::DarkGray),
-            );
-            ratatui::text::Line::from(vec![entered, current, rest])
+            let rest = pending(&line, true, theme.pending(), theme, highlight);
+            ratatui::text::Line::from([vec![entered], rest].concat())
         }
         Ordering::Less => {
-            let entered = Span::styled(
-                line.entered_text().unwrap_or("".to_owned()),
-                Style::default().bg(theme.bg()).fg(Color::Green),
-            );
             let current = Span::styled(
-                line.current_text()
-                    .map(String::from)
-                    .unwrap_or("".to_owned()),
-                Style::default().bg(theme.bg()).fg(Color::Green),
+                current_text,
+                Style::default().bg(theme.bg()).fg(theme.entered()),
             );
-            let rest = Span::styled(
-                line.rest_text().unwrap_or("".to_owned()),
-                Style::default().bg(theme.bg()).fg(Color::DarkGray),
-            );
-            ratatui::text::Line::from(vec![entered, current, rest])
+            let rest = pending(&line, false, theme.pending(), theme, highlight);
+            ratatui::text::Line::from([vec![entered, current], rest].concat())
+        }
+    }
+}
+
+fn pending<'a>(
+    line: &Line,
+    with_current: bool,
+    fg: Color,
+    theme: &Theme,
+    highlight: &Highlight,
+) -> Vec<Span<'a>> {
+    let rest = line.rest_text().unwrap_or("".to_owned());
+    let rest = match line.current_text() {
+        Some(c) if with_current => c + &rest,
+        _ => rest,
+    };
+
+    if highlight.has_line(line.line_no()) {
+        let entered = line.entered_text().unwrap_or("".to_owned());
+        let skip = entered.chars().count()
+            + match line.current_text() {
+                Some(c) if !with_current => c.chars().count(),
+                _ => 0,
+            };
+        highlight.spans(line.line_no(), skip, rest.chars().count(), theme.bg())
+    } else {
+        vec![Span::styled(rest, Style::default().bg(theme.bg()).fg(fg))]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::clock::Clock;
+    use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+    use std::time::Duration;
+
+    fn app(mode: Mode) -> App {
+        App::new("fn main() {\n}", Duration::from_secs(30), 20, false)
+            .unwrap()
+            .with_clock(Clock::manual())
+            .with_mode(mode)
+    }
+
+    fn source() -> Source {
+        Source {
+            path: PathBuf::from("main.rs"),
+            subject: None,
+            first_line: None,
+            seed: None,
         }
     }
+
+    fn render(widget: impl Widget, height: u16) -> Vec<String> {
+        let area = Rect::new(0, 0, 80, height);
+        let mut buffer = Buffer::empty(area);
+        widget.render(area, &mut buffer);
+        (0..height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn remaining_time() {
+        let theme = Theme::new("dark").unwrap();
+        let paused = app(Mode::Time).start().pause();
+        assert_eq!(
+            render(remaining_time_view(&paused, &theme), 1),
+            ["30 paused"]
+        );
+
+        let zen = app(Mode::Zen).start().tick().input('f');
+        assert_eq!(
+            render(remaining_time_view(&zen, &theme), 1),
+            ["1 0/2 lines"]
+        );
+    }
+
+    #[test]
+    fn presets() {
+        let theme = Theme::new("dark").unwrap();
+        assert_eq!(
+            render(time_view(&app(Mode::Words(10)), &theme), 1),
+            ["words 10 25 50 100"]
+        );
+        assert_eq!(render(time_view(&app(Mode::Zen), &theme), 1), ["zen"]);
+    }
+
+    #[test]
+    fn gutter() {
+        let mut source = source();
+        assert_eq!(super::gutter(&app(Mode::Time), &source), None);
+
+        source.first_line = Some(98);
+        assert_eq!(super::gutter(&app(Mode::Time), &source), Some((98, 2)));
+        source.first_line = Some(99);
+        assert_eq!(super::gutter(&app(Mode::Time), &source), Some((99, 3)));
+    }
+
+    #[test]
+    fn help() {
+        let theme = Theme::new("dark").unwrap();
+        let help = |mode: Mode, before_start: bool| {
+            render(
+                help_view(
+                    &theme,
+                    &source(),
+                    &KeyBindings::default(),
+                    mode,
+                    before_start,
+                ),
+                2,
+            )
+            .remove(1)
+        };
+
+        assert_eq!(
+            help(Mode::Words(10), true),
+            "r to restart, q to quit, ctrl-p to pause, left, right to select a word count"
+        );
+        assert_eq!(
+            help(Mode::Lines(10), true),
+            "r to restart, q to quit, ctrl-p to pause, left, right to select a line count"
+        );
+        assert_eq!(
+            help(Mode::Zen, true),
+            "r to restart, q to quit, ctrl-p to pause"
+        );
+        assert_eq!(help(Mode::Time, false), "r to restart, q to quit");
+    }
+
+    #[test]
+    fn seed() {
+        let theme = Theme::new("dark").unwrap();
+        let mut source = source();
+        source.seed = Some(42);
+        let help = render(
+            help_view(&theme, &source, &KeyBindings::default(), Mode::Time, false),
+            3,
+        );
+        assert_eq!(help[2], "main.rs  seed: 42");
+    }
 }
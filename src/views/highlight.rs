@@ -0,0 +1,114 @@
+use ratatui::{
+    style::{Color, Style},
+    text::Span,
+};
+use std::path::Path;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::Theme;
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+#[derive(Clone, Debug, Default)]
+pub struct Highlight {
+    lines: Vec<Vec<(Color, String)>>,
+}
+
+impl Highlight {
+    pub fn new(text: &str, path: &Path, theme: &Theme) -> Self {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let syntax = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(|e| syntax_set.find_syntax_by_extension(e))
+            .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+
+        let mut highlighter = HighlightLines::new(syntax, theme);
+        let lines = LinesWithEndings::from(text)
+            .map(|line| {
+                highlighter
+                    .highlight(line, &syntax_set)
+                    .iter()
+                    .map(|(style, s)| {
+                        (
+                            Color::Rgb(style.foreground.r, style.foreground.g, style.foreground.b),
+                            s.trim_end_matches(&['\r', '\n'][..]).to_string(),
+                        )
+                    })
+                    .filter(|(_, s)| !s.is_empty())
+                    .collect()
+            })
+            .collect();
+        Highlight { lines }
+    }
+
+    pub fn has_line(&self, line_no: usize) -> bool {
+        line_no > 0 && line_no <= self.lines.len()
+    }
+
+    // Spans of `take` characters of the line, starting at `skip`.
+    pub fn spans<'a>(&self, line_no: usize, skip: usize, take: usize, bg: Color) -> Vec<Span<'a>> {
+        let mut skip = skip;
+        let mut take = take;
+
+        self.lines
+            .get(line_no.wrapping_sub(1))
+            .map(|tokens| {
+                tokens
+                    .iter()
+                    .filter_map(|(color, token)| {
+                        let len = token.chars().count();
+
+                        if skip >= len {
+                            skip -= len;
+                            None
+                        } else if take == 0 {
+                            None
+                        } else {
+                            let text: String = token.chars().skip(skip).take(take).collect();
+                            take -= text.chars().count();
+                            skip = 0;
+                            Some(Span::styled(text, Style::default().bg(bg).fg(*color)))
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::views::theme;
+    use std::path::PathBuf;
+
+    #[test]
+    fn spans() {
+        let highlight = Highlight::new(
+            "fn main() {\n    let a = 1;\n}",
+            &PathBuf::from("main.rs"),
+            theme::Theme::new("dark").unwrap().syntax(),
+        );
+        assert!(highlight.has_line(3));
+        assert!(!highlight.has_line(4));
+
+        let text: String = highlight
+            .spans(2, 8, 4, Color::Black)
+            .iter()
+            .map(|s| s.content.to_string())
+            .collect();
+        assert_eq!(text, "a = ");
+        assert!(highlight.spans(1, 0, usize::MAX, Color::Black).len() > 1);
+    }
+
+    #[test]
+    fn empty() {
+        let highlight = Highlight::new(
+            "",
+            &PathBuf::from("main.rs"),
+            theme::Theme::new("dark").unwrap().syntax(),
+        );
+        assert!(!highlight.has_line(1));
+        assert!(highlight.spans(1, 0, usize::MAX, Color::Black).is_empty());
+    }
+}
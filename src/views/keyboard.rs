@@ -0,0 +1,268 @@
+use crate::types::keystroke::KeyStats;
+use crate::views::theme::Theme;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+use std::collections::HashMap;
+
+const KEY_WIDTH: u16 = 4;
+const ROW_OFFSETS: [u16; 4] = [0, 2, 3, 5];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Layout {
+    Qwerty,
+    Dvorak,
+    Colemak,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Metric {
+    Error,
+    Latency,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keyboard {
+    pub layout: Layout,
+    pub metric: Metric,
+}
+
+pub struct Heatmap<'a> {
+    keyboard: &'a Keyboard,
+    stats: &'a KeyStats,
+    theme: &'a Theme,
+}
+
+impl Layout {
+    pub const NAMES: [&'static str; 3] = ["qwerty", "dvorak", "colemak"];
+
+    pub fn new(layout: &str) -> Self {
+        match layout {
+            "dvorak" => Layout::Dvorak,
+            "colemak" => Layout::Colemak,
+            _ => Layout::Qwerty,
+        }
+    }
+
+    // (unshifted, shifted) characters of each row
+    fn rows(&self) -> [(&'static str, &'static str); 4] {
+        match self {
+            Layout::Qwerty => [
+                ("`1234567890-=", "~!@#$%^&*()_+"),
+                ("qwertyuiop[]\\", "QWERTYUIOP{}|"),
+                ("asdfghjkl;'", "ASDFGHJKL:\""),
+                ("zxcvbnm,./", "ZXCVBNM<>?"),
+            ],
+            Layout::Dvorak => [
+                ("`1234567890[]", "~!@#$%^&*(){}"),
+                ("',.pyfgcrl/=\\", "\"<>PYFGCRL?+|"),
+                ("aoeuidhtns-", "AOEUIDHTNS_"),
+                (";qjkxbmwvz", ":QJKXBMWVZ"),
+            ],
+            Layout::Colemak => [
+                ("`1234567890-=", "~!@#$%^&*()_+"),
+                ("qwfpgjluy;[]\\", "QWFPGJLUY:{}|"),
+                ("arstdhneio'", "ARSTDHNEIO\""),
+                ("zxcvbkm,./", "ZXCVBKM<>?"),
+            ],
+        }
+    }
+
+    fn key_of(&self, c: char) -> Option<char> {
+        match c {
+            ' ' | '\n' => Some(c),
+            _ => self.rows().iter().find_map(|(lower, upper)| {
+                lower
+                    .chars()
+                    .zip(upper.chars())
+                    .find(|(l, u)| *l == c || *u == c)
+                    .map(|(l, _)| l)
+            }),
+        }
+    }
+}
+
+impl Metric {
+    pub fn toggle(&self) -> Self {
+        match self {
+            Metric::Error => Metric::Latency,
+            Metric::Latency => Metric::Error,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::Error => "error rate",
+            Metric::Latency => "latency",
+        }
+    }
+}
+
+impl Keyboard {
+    pub fn new(layout: Layout) -> Self {
+        Keyboard {
+            layout,
+            metric: Metric::Error,
+        }
+    }
+
+    pub fn toggle_metric(mut self) -> Self {
+        self.metric = self.metric.toggle();
+        self
+    }
+
+    // Aggregates per-character stats onto physical keys and scales them to 0.0..=1.0.
+    pub fn heat(&self, stats: &KeyStats) -> HashMap<char, f64> {
+        let mut keys: HashMap<char, (usize, usize, u64)> = HashMap::new();
+
+        for stat in stats.keys() {
+            if let Some(key) = stat.key.chars().next().and_then(|c| self.layout.key_of(c)) {
+                let (count, errors, latency) = keys.entry(key).or_default();
+                *count += stat.count;
+                *errors += stat.errors;
+                *latency += stat.latency;
+            }
+        }
+
+        let values: HashMap<char, f64> = keys
+            .into_iter()
+            .map(|(key, (count, errors, latency))| {
+                let count = count.max(1) as f64;
+                match self.metric {
+                    Metric::Error => (key, errors as f64 / count),
+                    Metric::Latency => (key, latency as f64 / count),
+                }
+            })
+            .collect();
+        let max = values.values().fold(0.0_f64, |a, b| a.max(*b));
+
+        values
+            .into_iter()
+            .map(|(key, value)| (key, if max > 0.0 { value / max } else { 0.0 }))
+            .collect()
+    }
+}
+
+impl<'a> Heatmap<'a> {
+    pub fn new(keyboard: &'a Keyboard, stats: &'a KeyStats, theme: &'a Theme) -> Self {
+        Heatmap {
+            keyboard,
+            stats,
+            theme,
+        }
+    }
+
+    fn color(&self, heat: Option<&f64>) -> Color {
+        match heat {
+            Some(h) if *h < 0.5 => Color::Rgb((h * 2.0 * 255.0) as u8, 200, 0),
+            Some(h) => Color::Rgb(255, ((1.0 - h) * 2.0 * 200.0) as u8, 0),
+            None => self.theme.muted(),
+        }
+    }
+
+    fn key(&self, buf: &mut Buffer, x: u16, y: u16, label: &str, heat: Option<&f64>, area: Rect) {
+        let width = label.chars().count() as u16 + 2;
+
+        if x + width <= area.right() && y < area.bottom() {
+            buf.set_string(
+                x,
+                y,
+                format!(" {} ", label),
+                Style::default()
+                    .bg(self.color(heat))
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+    }
+}
+
+impl Widget for Heatmap<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let heat = self.keyboard.heat(self.stats);
+
+        buf.set_style(
+            area,
+            Style::default().bg(self.theme.bg()).fg(self.theme.fg()),
+        );
+        buf.set_string(
+            area.x,
+            area.y,
+            format!("heatmap: {} (tab to switch)", self.keyboard.metric.name()),
+            Style::default().bg(self.theme.bg()).fg(self.theme.muted()),
+        );
+
+        for (i, (lower, _)) in self.keyboard.layout.rows().iter().enumerate() {
+            let y = area.y + 1 + i as u16;
+            let x = area.x + ROW_OFFSETS[i];
+
+            for (j, c) in lower.chars().enumerate() {
+                self.key(
+                    buf,
+                    x + j as u16 * KEY_WIDTH,
+                    y,
+                    &c.to_string(),
+                    heat.get(&c),
+                    area,
+                );
+            }
+
+            if i == 2 {
+                self.key(
+                    buf,
+                    x + lower.chars().count() as u16 * KEY_WIDTH,
+                    y,
+                    "enter",
+                    heat.get(&'\n'),
+                    area,
+                );
+            }
+        }
+
+        self.key(
+            buf,
+            area.x + ROW_OFFSETS[3] + 3 * KEY_WIDTH,
+            area.y + 5,
+            &" ".repeat(KEY_WIDTH as usize * 5),
+            heat.get(&' '),
+            area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::keystroke::Keystroke;
+
+    #[test]
+    fn key_of() {
+        assert_eq!(Layout::Qwerty.key_of('{'), Some('['));
+        assert_eq!(Layout::Qwerty.key_of('A'), Some('a'));
+        assert_eq!(Layout::Dvorak.key_of('<'), Some(','));
+        assert_eq!(Layout::Colemak.key_of(':'), Some(';'));
+        assert_eq!(Layout::Qwerty.key_of('\n'), Some('\n'));
+        assert_eq!(Layout::Qwerty.key_of('日'), None);
+    }
+
+    #[test]
+    fn heat() {
+        let stats = KeyStats::new(&[
+            Keystroke::new(":", ';', 100, false),
+            Keystroke::new(";", ';', 300, true),
+            Keystroke::new("a", 'a', 100, true),
+        ]);
+
+        let keyboard = Keyboard::new(Layout::Qwerty);
+        let heat = keyboard.heat(&stats);
+        assert_eq!(heat.get(&';'), Some(&1.0));
+        assert_eq!(heat.get(&'a'), Some(&0.0));
+
+        let heat = keyboard.toggle_metric().heat(&stats);
+        assert_eq!(heat.get(&';'), Some(&1.0));
+        assert_eq!(heat.get(&'a'), Some(&0.5));
+    }
+}
@@ -0,0 +1,217 @@
+use super::theme::Theme;
+use crate::picker::{Match, Picker};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+pub fn picker_view(f: &mut Frame, picker: &Picker, theme: &Theme) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(2)].as_ref())
+        .split(f.area());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(rows[0]);
+    let list = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)].as_ref())
+        .split(columns[0]);
+
+    f.render_widget(query_view(picker, theme), list[0]);
+    f.render_widget(
+        matches_view(picker, list[1].height as usize, theme),
+        list[1],
+    );
+    f.render_widget(
+        preview_view(picker, columns[1].height as usize, theme),
+        columns[1],
+    );
+    f.render_widget(help_view(theme), rows[1]);
+}
+
+fn query_view<'a>(picker: &Picker, theme: &Theme) -> Paragraph<'a> {
+    Paragraph::new(Line::from(vec![
+        Span::styled(
+            "> ",
+            Style::default()
+                .bg(theme.bg())
+                .fg(theme.help_key())
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            picker.query().to_owned(),
+            Style::default().bg(theme.bg()).fg(theme.fg()),
+        ),
+        Span::styled(
+            format!("  {}/{}", picker.matches().len(), picker.total()),
+            Style::default().bg(theme.bg()).fg(theme.muted()),
+        ),
+    ]))
+    .style(Style::default().bg(theme.bg()).fg(theme.fg()))
+    .block(
+        Block::default()
+            .borders(Borders::BOTTOM)
+            .style(Style::default().bg(theme.bg()).fg(theme.fg())),
+    )
+}
+
+fn matches_view<'a>(picker: &Picker, height: usize, theme: &Theme) -> Paragraph<'a> {
+    // keep the selected row in view
+    let skip = (picker.selected() + 1).saturating_sub(height);
+
+    Paragraph::new(
+        picker
+            .matches()
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(height)
+            .map(|(i, m)| match_line(m, i == picker.selected(), theme))
+            .collect::<Vec<Line>>(),
+    )
+    .style(Style::default().bg(theme.bg()).fg(theme.fg()))
+}
+
+fn match_line<'a>(m: &Match, selected: bool, theme: &Theme) -> Line<'a> {
+    let base = if selected {
+        Style::default()
+            .bg(theme.bg())
+            .fg(theme.fg())
+            .add_modifier(Modifier::REVERSED)
+    } else {
+        Style::default().bg(theme.bg()).fg(theme.fg())
+    };
+
+    Line::from(
+        m.label
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if m.indices.contains(&i) {
+                    Span::styled(
+                        c.to_string(),
+                        base.fg(theme.help_key()).add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    Span::styled(c.to_string(), base)
+                }
+            })
+            .collect::<Vec<Span>>(),
+    )
+}
+
+fn preview_view<'a>(picker: &Picker, height: usize, theme: &Theme) -> Paragraph<'a> {
+    let preview = match picker.preview() {
+        Some(preview) => preview,
+        None => {
+            return Paragraph::new(Span::styled(
+                "no matching files",
+                Style::default().bg(theme.bg()).fg(theme.muted()),
+            ))
+            .style(Style::default().bg(theme.bg()).fg(theme.fg()))
+            .alignment(Alignment::Center)
+        }
+    };
+    let width = preview.lines.to_string().len();
+    let header = Line::from(Span::styled(
+        format!("{} · {} lines", preview.language, preview.lines),
+        Style::default().bg(theme.bg()).fg(theme.muted()),
+    ));
+
+    Paragraph::new(
+        [
+            vec![header],
+            preview
+                .text
+                .lines()
+                .take(height.saturating_sub(1))
+                .enumerate()
+                .map(|(i, line)| {
+                    Line::from(vec![
+                        Span::styled(
+                            format!("{:>width$} ", i + 1, width = width),
+                            Style::default().bg(theme.bg()).fg(theme.muted()),
+                        ),
+                        Span::styled(
+                            line.to_owned(),
+                            Style::default().bg(theme.bg()).fg(theme.pending()),
+                        ),
+                    ])
+                })
+                .collect(),
+        ]
+        .concat(),
+    )
+    .style(Style::default().bg(theme.bg()).fg(theme.fg()))
+    .block(
+        Block::default()
+            .borders(Borders::LEFT)
+            .style(Style::default().bg(theme.bg()).fg(theme.muted())),
+    )
+}
+
+fn help_view<'a>(theme: &Theme) -> Paragraph<'a> {
+    let key = |k: &'a str| {
+        Span::styled(
+            k,
+            Style::default()
+                .bg(theme.bg())
+                .fg(theme.help_key())
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+    let text = |t: &'a str| Span::styled(t, Style::default().bg(theme.bg()).fg(theme.muted()));
+
+    Paragraph::new(Line::from(vec![
+        key("enter"),
+        text(" to start, "),
+        key("ctrl-r"),
+        text(" for a random file, "),
+        key("up, down"),
+        text(" to select, "),
+        key("esc"),
+        text(" to quit"),
+    ]))
+    .style(Style::default().bg(theme.bg()).fg(theme.fg()))
+    .block(
+        Block::default()
+            .borders(Borders::TOP)
+            .style(Style::default().bg(theme.bg()).fg(theme.fg())),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::path::PathBuf;
+
+    #[test]
+    fn render() {
+        let theme = Theme::new("dark").unwrap();
+        let picker = Picker::new(
+            vec![PathBuf::from("Cargo.toml"), PathBuf::from("src/main.rs")],
+            |p| Ok(format!("[package]\nname = \"{}\"\n", p.display())),
+        );
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        terminal.draw(|f| picker_view(f, &picker, &theme)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| {
+            (0..buffer.area.width)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+        };
+        assert!(row(0).starts_with(">   2/2"));
+        assert!(row(2).starts_with("Cargo.toml"));
+        assert!(row(3).starts_with("src/main.rs"));
+        assert!(row(0).contains("Plain Text · "));
+        assert!(row(1).contains("1 [package]"));
+        assert!(row(9).starts_with("enter to start"));
+    }
+}
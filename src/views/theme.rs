@@ -0,0 +1,348 @@
+use anyhow::{anyhow, Result};
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use syntect::highlighting::{self, Highlighter, ThemeSet};
+use syntect::parsing::Scope;
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Colors {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub entered: Option<String>,
+    pub current: Option<String>,
+    pub error: Option<String>,
+    pub pending: Option<String>,
+    pub muted: Option<String>,
+    pub chart_wpm: Option<String>,
+    pub chart_acc: Option<String>,
+    pub help_key: Option<String>,
+}
+
+// A theme file in TOML, e.g.
+//
+//   base = "light"
+//   syntax = "InspiredGitHub"
+//
+//   [colors]
+//   entered = "blue"
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThemeFile {
+    base: Option<String>,
+    syntax: Option<String>,
+    colors: Colors,
+}
+
+#[derive(Clone, Debug)]
+pub struct Theme {
+    fg: Color,
+    bg: Color,
+    entered: Color,
+    current: Color,
+    error: Color,
+    pending: Color,
+    muted: Color,
+    chart_wpm: Color,
+    chart_acc: Color,
+    help_key: Color,
+    syntax: highlighting::Theme,
+}
+
+impl Theme {
+    // `theme` is "dark", "light", the name of a bundled syntect theme,
+    // or a path to a .toml or .tmTheme file.
+    pub fn new(theme: &str) -> Result<Self> {
+        let path = Path::new(theme);
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Theme::from_toml(path),
+            Some("tmTheme") => Theme::from_tm_theme(path),
+            _ => Theme::named(theme),
+        }
+    }
+
+    fn named(name: &str) -> Result<Self> {
+        match name {
+            "dark" => Ok(Theme::base(
+                Color::White,
+                Color::Black,
+                Theme::bundled("base16-ocean.dark")?,
+            )),
+            "light" => Ok(Theme::base(
+                Color::Black,
+                Color::White,
+                Theme::bundled("base16-ocean.light")?,
+            )),
+            _ => Ok(Theme::from_syntax(Theme::bundled(name)?)),
+        }
+    }
+
+    fn base(fg: Color, bg: Color, syntax: highlighting::Theme) -> Self {
+        Theme {
+            fg,
+            bg,
+            entered: Color::Green,
+            current: Color::Green,
+            error: Color::Red,
+            pending: Color::DarkGray,
+            muted: Color::DarkGray,
+            chart_wpm: Color::Yellow,
+            chart_acc: Color::DarkGray,
+            help_key: Color::Yellow,
+            syntax,
+        }
+    }
+
+    fn bundled(name: &str) -> Result<highlighting::Theme> {
+        let mut theme_set = ThemeSet::load_defaults();
+        theme_set.themes.remove(name).ok_or_else(|| {
+            anyhow!(
+                "Unknown theme: {} (available: dark, light, {})",
+                name,
+                theme_set
+                    .themes
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        })
+    }
+
+    fn from_toml(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let file: ThemeFile = toml::from_str(&text)
+            .map_err(|e| anyhow!("Invalid theme {}: {}", path.display(), e))?;
+        let mut theme = Theme::named(file.base.as_deref().unwrap_or("dark"))?;
+
+        if let Some(syntax) = file.syntax {
+            theme.syntax = match Path::new(&syntax).extension().and_then(|e| e.to_str()) {
+                Some("tmTheme") => Theme::load_tm_theme(
+                    &path.parent().unwrap_or_else(|| Path::new("")).join(syntax),
+                )?,
+                _ => Theme::bundled(&syntax)?,
+            };
+        }
+
+        theme.with_colors(&file.colors)
+    }
+
+    fn from_tm_theme(path: &Path) -> Result<Self> {
+        Ok(Theme::from_syntax(Theme::load_tm_theme(path)?))
+    }
+
+    fn load_tm_theme(path: &Path) -> Result<highlighting::Theme> {
+        ThemeSet::get_theme(path).map_err(|e| anyhow!("Invalid theme {}: {}", path.display(), e))
+    }
+
+    // Derives the slots from the scopes of a syntax theme, falling back to the default colors.
+    fn from_syntax(syntax: highlighting::Theme) -> Self {
+        let highlighter = Highlighter::new(&syntax);
+        let default = highlighter.get_default();
+        let scope = |name: &str| {
+            Scope::new(name)
+                .ok()
+                .map(|s| highlighter.style_for_stack(&[s]).foreground)
+                .filter(|c| *c != default.foreground)
+                .map(Theme::color)
+        };
+
+        let entered = scope("string").unwrap_or(Color::Green);
+        let pending = scope("comment").unwrap_or(Color::DarkGray);
+        Theme {
+            fg: Theme::color(default.foreground),
+            bg: Theme::color(default.background),
+            entered,
+            current: syntax.settings.caret.map(Theme::color).unwrap_or(entered),
+            error: scope("invalid").unwrap_or(Color::Red),
+            pending,
+            muted: pending,
+            chart_wpm: scope("constant.numeric").unwrap_or(Color::Yellow),
+            chart_acc: pending,
+            help_key: scope("keyword").unwrap_or(Color::Yellow),
+            syntax: syntax.clone(),
+        }
+    }
+
+    fn color(color: highlighting::Color) -> Color {
+        Color::Rgb(color.r, color.g, color.b)
+    }
+
+    pub fn with_colors(mut self, colors: &Colors) -> Result<Self> {
+        let slots = [
+            (&mut self.fg, &colors.fg),
+            (&mut self.bg, &colors.bg),
+            (&mut self.entered, &colors.entered),
+            (&mut self.current, &colors.current),
+            (&mut self.error, &colors.error),
+            (&mut self.pending, &colors.pending),
+            (&mut self.muted, &colors.muted),
+            (&mut self.chart_wpm, &colors.chart_wpm),
+            (&mut self.chart_acc, &colors.chart_acc),
+            (&mut self.help_key, &colors.help_key),
+        ];
+
+        for (slot, color) in slots {
+            if let Some(c) = color {
+                *slot = Color::from_str(c).map_err(|_| anyhow!("Invalid color: {}", c))?;
+            }
+        }
+
+        Ok(self)
+    }
+
+    pub fn fg(&self) -> Color {
+        self.fg
+    }
+
+    pub fn bg(&self) -> Color {
+        self.bg
+    }
+
+    pub fn entered(&self) -> Color {
+        self.entered
+    }
+
+    pub fn current(&self) -> Color {
+        self.current
+    }
+
+    pub fn error(&self) -> Color {
+        self.error
+    }
+
+    pub fn pending(&self) -> Color {
+        self.pending
+    }
+
+    pub fn muted(&self) -> Color {
+        self.muted
+    }
+
+    pub fn chart_wpm(&self) -> Color {
+        self.chart_wpm
+    }
+
+    pub fn chart_acc(&self) -> Color {
+        self.chart_acc
+    }
+
+    pub fn help_key(&self) -> Color {
+        self.help_key
+    }
+
+    pub fn syntax(&self) -> &highlighting::Theme {
+        &self.syntax
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn named() {
+        let theme = Theme::new("light").unwrap();
+        assert_eq!(theme.fg(), Color::Black);
+        assert_eq!(theme.entered(), Color::Green);
+        assert_eq!(theme.syntax().name.as_deref(), Some("Base16 Ocean Light"));
+
+        let theme = Theme::new("Solarized (dark)").unwrap();
+        assert_eq!(theme.bg(), Color::Rgb(0x00, 0x2b, 0x36));
+        assert_ne!(theme.entered(), Color::Green);
+
+        assert!(Theme::new("unknown").is_err());
+    }
+
+    #[test]
+    fn with_colors() {
+        let colors = Colors {
+            entered: Some("blue".to_owned()),
+            help_key: Some("#ff0000".to_owned()),
+            ..Colors::default()
+        };
+        let theme = Theme::new("dark").unwrap().with_colors(&colors).unwrap();
+        assert_eq!(theme.entered(), Color::Blue);
+        assert_eq!(theme.help_key(), Color::Rgb(255, 0, 0));
+        assert_eq!(theme.error(), Color::Red);
+
+        let colors = Colors {
+            error: Some("not a color".to_owned()),
+            ..Colors::default()
+        };
+        assert!(Theme::new("dark").unwrap().with_colors(&colors).is_err());
+    }
+
+    #[test]
+    fn toml_file() {
+        let dir = TempDir::new("theme-toml");
+        let path = dir.file(
+            "theme.toml",
+            r##"
+base = "light"
+syntax = "InspiredGitHub"
+
+[colors]
+pending = "#808080"
+"##,
+        );
+        let theme = Theme::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(theme.bg(), Color::White);
+        assert_eq!(theme.pending(), Color::Rgb(128, 128, 128));
+        assert_eq!(theme.syntax().name.as_deref(), Some("GitHub"));
+
+        let path = dir.file("invalid.toml", "unknown = 1");
+        assert!(Theme::new(path.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn tm_theme_file() {
+        let dir = TempDir::new("theme-tm");
+        let path = dir.file(
+            "theme.tmTheme",
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+  <key>name</key><string>Test</string>
+  <key>settings</key>
+  <array>
+    <dict>
+      <key>settings</key>
+      <dict>
+        <key>foreground</key><string>#eeeeee</string>
+        <key>background</key><string>#111111</string>
+        <key>caret</key><string>#ff00ff</string>
+      </dict>
+    </dict>
+    <dict>
+      <key>scope</key><string>string</string>
+      <key>settings</key>
+      <dict><key>foreground</key><string>#00ff00</string></dict>
+    </dict>
+    <dict>
+      <key>scope</key><string>comment</string>
+      <key>settings</key>
+      <dict><key>foreground</key><string>#777777</string></dict>
+    </dict>
+  </array>
+</dict>
+</plist>"#,
+        );
+        let theme = Theme::new(path.to_str().unwrap()).unwrap();
+        assert_eq!(theme.fg(), Color::Rgb(0xee, 0xee, 0xee));
+        assert_eq!(theme.bg(), Color::Rgb(0x11, 0x11, 0x11));
+        assert_eq!(theme.entered(), Color::Rgb(0, 255, 0));
+        assert_eq!(theme.current(), Color::Rgb(255, 0, 255));
+        assert_eq!(theme.pending(), Color::Rgb(0x77, 0x77, 0x77));
+        assert_eq!(theme.error(), Color::Red);
+        assert_eq!(theme.syntax().name.as_deref(), Some("Test"));
+
+        assert!(Theme::new("/nonexistent/theme.tmTheme").is_err());
+    }
+}